@@ -2,6 +2,8 @@ use ::std::thread;
 
 use ::criterion::{black_box, Criterion, criterion_group, criterion_main};
 use ::tilde::CliOperation;
+use ::tilde::ExecConfig;
+use ::tilde::IoConfig;
 use ::tilde::run_tilde;
 use ::tilde::TildeArgs;
 
@@ -10,7 +12,7 @@ use ::tilde::TildeArgs;
 pub fn criterion_benchmark(c: &mut Criterion) {
     let source = ",hello world";
     c.bench_function("analyze_tilde_code", |b| b.iter(|| thread::spawn(||
-        run_tilde(black_box(&TildeArgs { operation: CliOperation::Analyze(source.to_string()) }))
+        run_tilde(black_box(&TildeArgs { operation: CliOperation::Analyze(source.to_string()), config: ExecConfig::default(), io: IoConfig::default() }))
     ).join().unwrap()));
 }
 
@@ -0,0 +1,11 @@
+use ::tilde::tilde_strs;
+
+fn main() {
+    // a block pushes a closure with the code between braces
+    let res = tilde_strs("3 { dup mul } apply", "").unwrap();
+    assert_eq!(res, "9");
+
+    // blocks can be nested, and are applied implicitly at the end like other closures
+    let res = tilde_strs("4 { { 1 plus } apply 2 mul }", "").unwrap();
+    assert_eq!(res, "10");
+}
@@ -0,0 +1,30 @@
+use ::tilde::Array;
+use ::tilde::ExecConfig;
+use ::tilde::ErrorKind;
+use ::tilde::Nr;
+use ::tilde::RunMode;
+use ::tilde::Text;
+use ::tilde::tilde_eval;
+use ::tilde::tilde_eval_stack;
+use ::tilde::Value;
+
+fn main() {
+    // pass structured values in and get the top value back
+    let inp = Value::Arr(Array::of(vec![Nr::new(1.0), Nr::new(2.0), Nr::new(3.0)]));
+    let res = tilde_eval("sum", inp, RunMode::Any, &ExecConfig::default()).unwrap();
+    assert_eq!(res, Value::Num(Nr::new(6.0)));
+
+    // get the whole final stack, top value last
+    let inp = Value::Txt(Text::of("hello"));
+    let res = tilde_eval_stack("dup 2 3 plus", inp, RunMode::Any, &ExecConfig::default()).unwrap();
+    assert_eq!(res, vec![
+        Value::Txt(Text::of("hello")),
+        Value::Txt(Text::of("hello")),
+        Value::Num(Nr::new(5.0)),
+    ]);
+
+    // limits apply just like on the command line
+    let config = ExecConfig { max_steps: Some(2), ..ExecConfig::default() };
+    let err = tilde_eval("1 2 plus", Value::default(), RunMode::Any, &config).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::StepLimit { steps: 2 });
+}
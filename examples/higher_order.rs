@@ -0,0 +1,17 @@
+use ::tilde::tilde_strs;
+
+fn main() {
+    // squares of the odd numbers below 10
+    assert_eq!(tilde_strs("drop 10 range { 2 mod } filter { dup mul } map", "").unwrap(), "[1,9,25,49,81]");
+
+    // product of 1 to 5, and the same with a starting value
+    assert_eq!(tilde_strs("drop 5 range-to { mul } reduce", "").unwrap(), "120");
+    assert_eq!(tilde_strs("drop 5 range-to 1 { mul } fold", "").unwrap(), "120");
+
+    // texts are handled character by character
+    assert_eq!(tilde_strs("drop 'a-b-c' { '-' neq } filter", "").unwrap(), "abc");
+    assert_eq!(tilde_strs("drop 'a-b-c' { '-' eq } count-where", "").unwrap(), "2");
+
+    // closures that leave several values are joined by flat-map
+    assert_eq!(tilde_strs("drop 3 range { dup } flat-map", "").unwrap(), "[0,0,1,1,2,2]");
+}
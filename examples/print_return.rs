@@ -0,0 +1,11 @@
+use ::tilde::tilde_strs;
+
+fn main() {
+    // print writes a value on its own line right away, and the final result is written after it
+    let res = tilde_strs("drop 'answer:' print 42", "").unwrap();
+    assert_eq!(res, "answer:\n42");
+
+    // return stops the program, and the rest is not run
+    let res = tilde_strs("drop 1 { 2 plus return } { 3 plus } if 10 mul", "").unwrap();
+    assert_eq!(res, "3");
+}
@@ -0,0 +1,21 @@
+use ::tilde::ExecConfig;
+use ::tilde::IoConfig;
+use ::tilde::OutputMode;
+use ::tilde::RunMode;
+use ::tilde::tilde_strs_mode;
+
+fn main() {
+    // arguments are an array of texts below the input lines
+    let io = IoConfig {
+        output: OutputMode::Stack,
+        args: Some(vec!["a".to_owned(), "b c".to_owned()]),
+        ..IoConfig::default()
+    };
+    let res = tilde_strs_mode("", "x\ny", RunMode::Any, &io, &ExecConfig::default()).unwrap();
+    assert_eq!(res, "[a,b c]\n[x,y]");
+
+    // swap to use the arguments instead of the input
+    let io = IoConfig { args: Some(vec!["3".to_owned()]), ..IoConfig::default() };
+    let res = tilde_strs_mode("drop count", "", RunMode::Any, &io, &ExecConfig::default()).unwrap();
+    assert_eq!(res, "1");
+}
@@ -0,0 +1,16 @@
+use ::tilde::tilde_strs;
+
+fn main() {
+    // counting from zero, from one and down
+    assert_eq!(tilde_strs("drop 4 range", "").unwrap(), "[0,1,2,3]");
+    assert_eq!(tilde_strs("drop 4 range-to sum", "").unwrap(), "10");
+    assert_eq!(tilde_strs("drop 3 range-down", "").unwrap(), "[2,1,0]");
+
+    // between two numbers, and with a step
+    assert_eq!(tilde_strs("drop 5 2 range-between", "").unwrap(), "[5,4,3]");
+    assert_eq!(tilde_strs("drop 1 10 3 range-step", "").unwrap(), "[1,4,7]");
+
+    // indices of the characters of a text, or of the input lines
+    assert_eq!(tilde_strs("drop 'abc' range", "").unwrap(), "[0,1,2]");
+    assert_eq!(tilde_strs("range", "a\nb").unwrap(), "[0,1]");
+}
@@ -0,0 +1,15 @@
+use ::tilde::tilde_strs;
+
+fn main() {
+    // factorial, where recurse applies the function given to fix again
+    let res = tilde_strs("drop 6 { { dup 1 minus recurse mul } 1 if } fix", "").unwrap();
+    assert_eq!(res, "720");
+
+    // fibonacci is exponential without remembering results, but fast with fix-memo
+    let prog = "drop 30 { { 1 minus { dup recurse swap 1 minus recurse plus } 1 if } 3 3 minus if } fix-memo";
+    let res = tilde_strs(prog, "").unwrap();
+    assert_eq!(res, "832040");
+
+    // recursion that does not end stops at the depth limit
+    assert!(tilde_strs("drop 1 { recurse } fix", "").is_err());
+}
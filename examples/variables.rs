@@ -0,0 +1,11 @@
+use ::tilde::tilde_strs;
+
+fn main() {
+    // store a value and use it again later, after it has left the stack
+    let res = tilde_strs("3 =x drop 4 x mul", "").unwrap();
+    assert_eq!(res, "12");
+
+    // registers a to e can be read before storing, which gives the program input
+    let res = tilde_strs("drop a count", "x\ny\nz").unwrap();
+    assert_eq!(res, "3");
+}
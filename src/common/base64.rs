@@ -3,6 +3,8 @@ use ::base64::engine::general_purpose::URL_SAFE_NO_PAD;
 
 use crate::compile::Letter;
 use crate::compile::LetterKind;
+use crate::ErrorKind;
+use crate::TildeError;
 use crate::TildeRes;
 
 pub fn b64_encode(source: &[Letter]) -> TildeRes<String> {
@@ -27,7 +29,7 @@ pub fn b64_encode(source: &[Letter]) -> TildeRes<String> {
 
 pub fn b64_decode(base64_source: &str) -> TildeRes<Vec<Letter>> {
     let Ok(src_bytes) = URL_SAFE_NO_PAD.decode(base64_source) else {
-        return Err("base64 encoding not valid, alphabet should be A-Za-z0-9-_ without padding".to_string());
+        return Err(TildeError::new(ErrorKind::Parse("base64 encoding not valid, alphabet should be A-Za-z0-9-_ without padding".to_string())));
     };
     let mut letters = Vec::with_capacity(src_bytes.len() * 2);
     let possible_letter_cnt = Letter::option_count() as u8;
@@ -0,0 +1,192 @@
+use ::std::error;
+use ::std::fmt;
+use ::std::time::Duration;
+
+use crate::compile::DecodeError;
+
+/// Range of chars in the source code, start inclusive and end exclusive.
+/// In golf mode, each char is one letter, so this is also the letter range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        debug_assert!(start <= end);
+        Span { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Source code could not be parsed.
+    Parse(String),
+    /// Golfed literal could not be decoded.
+    Decode(DecodeError),
+    /// Source uses something that is not allowed or not available in golf mode.
+    GolfOnly(String),
+    /// Operation does not support the types of the values it got from the stack.
+    Type { op: String, types: Vec<&'static str> },
+    /// Execution used more steps than allowed; `steps` is how many were completed.
+    StepLimit { steps: u64 },
+    /// Execution took longer than allowed; `steps` is how many were completed.
+    Timeout { steps: u64, elapsed: Duration },
+    /// Execution created larger arrays or texts than allowed; `size` is the total created so far.
+    SizeLimit { steps: u64, size: u64 },
+    /// Recursion through `fix` was nested deeper than allowed; `depth` is the limit.
+    DepthLimit { depth: usize },
+    /// Not a real problem: the program used `return` to stop early, which the executor turns into the result.
+    Return,
+    /// Any other problem while executing the program.
+    Runtime(String),
+    /// Input could not be converted to a value in the selected input mode.
+    Input(String),
+    /// Reading input or writing output failed.
+    Io(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TildeError {
+    kind: ErrorKind,
+    span: Option<Span>,
+    op_index: Option<usize>,
+}
+
+impl TildeError {
+    pub fn new(kind: ErrorKind) -> Self {
+        TildeError { kind, span: None, op_index: None }
+    }
+
+    pub fn parse(msg: impl Into<String>, span: Span) -> Self {
+        TildeError::new(ErrorKind::Parse(msg.into())).with_span(span)
+    }
+
+    pub fn decode(err: DecodeError, span: Span) -> Self {
+        TildeError::new(ErrorKind::Decode(err)).with_span(span)
+    }
+
+    pub fn golf_only(msg: impl Into<String>, span: Option<Span>) -> Self {
+        TildeError { kind: ErrorKind::GolfOnly(msg.into()), span, op_index: None }
+    }
+
+    pub fn typ(op: impl Into<String>, types: &[&'static str]) -> Self {
+        TildeError::new(ErrorKind::Type { op: op.into(), types: types.to_vec() })
+    }
+
+    pub fn runtime(msg: impl Into<String>) -> Self {
+        TildeError::new(ErrorKind::Runtime(msg.into()))
+    }
+
+    pub fn io(msg: impl Into<String>) -> Self {
+        TildeError::new(ErrorKind::Io(msg.into()))
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Attach the position of the operation that failed, unless a more specific one is known.
+    pub fn at_op(mut self, op_index: usize, span: Option<Span>) -> Self {
+        if self.op_index.is_none() {
+            self.op_index = Some(op_index);
+            self.span = self.span.or(span);
+        }
+        self
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    pub fn op_index(&self) -> Option<usize> {
+        self.op_index
+    }
+
+    /// Show the source line that contains the error, with carets under the problematic part.
+    pub fn source_snippet(&self, source: &str) -> Option<String> {
+        let span = self.span?;
+        let mut line_start = 0;
+        for line in source.split('\n') {
+            let line_len = line.chars().count();
+            if span.start <= line_start + line_len {
+                let offset = span.start - line_start;
+                let width = span.len().clamp(1, (line_len - offset).max(1));
+                return Some(format!("{}\n{}{}", line, " ".repeat(offset), "^".repeat(width)))
+            }
+            line_start += line_len + 1;
+        }
+        None
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Parse(msg) => write!(f, "{msg}"),
+            ErrorKind::Decode(err) => write!(f, "could not parse golfed literal, err: {err}"),
+            ErrorKind::GolfOnly(msg) => write!(f, "{msg}"),
+            ErrorKind::Type { op, types } => write!(f, "operation '{op}' does not support input types ({})", types.join(", ")),
+            ErrorKind::StepLimit { steps } => write!(f, "execution stopped after {steps} steps because the step limit was reached"),
+            ErrorKind::Timeout { steps, elapsed } => write!(f, "execution stopped after {steps} steps and {} ms because it timed out", elapsed.as_millis()),
+            ErrorKind::SizeLimit { steps, size } => write!(f, "execution stopped after {steps} steps because the size limit was reached (created {size} elements or bytes)"),
+            ErrorKind::DepthLimit { depth } => write!(f, "execution stopped because recursion was nested more than {depth} levels deep"),
+            ErrorKind::Return => write!(f, "'return' was used outside of a running program"),
+            ErrorKind::Runtime(msg) => write!(f, "{msg}"),
+            ErrorKind::Input(msg) => write!(f, "{msg}"),
+            ErrorKind::Io(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl fmt::Display for TildeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        match (self.op_index, self.span) {
+            (Some(op_index), Some(span)) => write!(f, " (at operation #{op_index}, source position {}-{})", span.start, span.end),
+            (Some(op_index), None) => write!(f, " (at operation #{op_index})"),
+            (None, Some(span)) => write!(f, " (at source position {}-{})", span.start, span.end),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl error::Error for TildeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snippet_first_line() {
+        let err = TildeError::parse("unrecognized: x", Span::new(4, 5));
+        assert_eq!(err.source_snippet("dup x drop").unwrap(), "dup x drop\n    ^");
+    }
+
+    #[test]
+    fn snippet_later_line() {
+        let err = TildeError::parse("unknown", Span::new(8, 12));
+        assert_eq!(err.source_snippet("1 2\n3 4 nope 5").unwrap(), "3 4 nope 5\n    ^^^^");
+    }
+
+    #[test]
+    fn snippet_at_end() {
+        let err = TildeError::parse("unexpected end", Span::new(3, 3));
+        assert_eq!(err.source_snippet("abc").unwrap(), "abc\n   ^");
+    }
+
+    #[test]
+    fn snippet_without_span() {
+        assert!(TildeError::io("broken pipe").source_snippet("abc").is_none());
+    }
+}
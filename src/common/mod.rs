@@ -1,5 +1,8 @@
 pub use self::base64::b64_decode;
 pub use self::base64::b64_encode;
+pub use self::error::ErrorKind;
+pub use self::error::Span;
+pub use self::error::TildeError;
 pub use self::escape_str::escape_for_string;
 pub use self::escape_str::is_safe_for_string;
 pub use self::log::log;
@@ -14,3 +17,4 @@ mod text_trans;
 mod tiny_map;
 mod escape_str;
 mod base64;
+mod error;
@@ -7,6 +7,8 @@ use ::tinyvec::TinyVecIterator;
 
 use crate::compile::Letter;
 use crate::compile::LetterKind;
+use crate::ErrorKind;
+use crate::TildeError;
 use crate::TildeRes;
 
 // max length does not apply to literals, which are unbounded,
@@ -32,18 +34,18 @@ impl GolfWord {
             VariableOpen => {
                 for following in letters.iter().skip(1) {
                     if following.kind() != Modifier {
-                        return Err(format!("golf identifier starting with variable opener ({}) must be followed by only modifiers (found '{}')", letters[0], following))
+                        return Err(TildeError::new(ErrorKind::Parse(format!("golf identifier starting with variable opener ({}) must be followed by only modifiers (found '{}')", letters[0], following))))
                     }
                 }
             }
             FixedOpen => {
                 for following in letters.iter().skip(2) {
                     if following.kind() != Modifier {
-                        return Err(format!("golf identifier starting with variable opener ({}) must be followed by only modifiers (found '{}')", letters[0], following))
+                        return Err(TildeError::new(ErrorKind::Parse(format!("golf identifier starting with variable opener ({}) must be followed by only modifiers (found '{}')", letters[0], following))))
                     }
                 }
             }
-            Modifier => return Err(format!("golf identifier cannot start with modifier token {}", letters[0]))
+            Modifier => return Err(TildeError::new(ErrorKind::Parse(format!("golf identifier cannot start with modifier token {}", letters[0]))))
         }
         let hash = calculate_id(&letters);
         Ok(GolfWord {
@@ -1,33 +1,79 @@
+use ::std::collections::HashSet;
 use ::std::fmt::Debug;
+use ::std::mem;
 
 use crate::{Nr, RunMode, tilde_log};
+use crate::common::Span;
 use crate::compile::{GolfWord, Letter, LetterKind};
 use crate::compile::golf_word::GolfWordContent;
 use crate::compile::op_lookup::{lookup_op_golf, lookup_op_long};
 use crate::compile::Prog;
 use crate::compile::text_literal::decode_str;
-use crate::op::{NumberOp, TextOp};
+use crate::op::{BlockOp, is_register, Load, lookup_register_golf, NumberOp, Op, Store, TextOp};
+use crate::TildeError;
 use crate::TildeRes;
 
 pub fn parse(src: &str, mode: RunMode) -> TildeRes<Prog> {
+    parse_with_names(src, mode, &mut HashSet::new())
+}
+
+/// Like [parse], but the source can also load the variables in `stored_names`, for example
+/// those stored by earlier code. Names of variables stored by the source are added to it.
+pub fn parse_with_names(src: &str, mode: RunMode, stored_names: &mut HashSet<String>) -> TildeRes<Prog> {
     let mut ops = vec![];
+    let mut spans = vec![];
     let mut rev_tokens = src
         .chars()
         .collect::<Vec<_>>();
     rev_tokens.reverse();
-    tilde_log!("parsing {} tokens", rev_tokens.len());
+    let token_cnt = rev_tokens.len();
+    tilde_log!("parsing {} tokens", token_cnt);
     let mut string_buffer = String::new();
     let mut letters_buffer = Vec::new();
     let mut string_decode_buffer = Vec::new();
+    let mut open_blocks = Vec::new();
     while let Some(current) = rev_tokens.pop() {
+        let start = token_cnt - rev_tokens.len() - 1;
+        let span_to_here = |rev_tokens: &Vec<char>| Span::new(start, token_cnt - rev_tokens.len());
         if current.is_whitespace() {
             if mode.golf_only() {
-                return Err("golf-only mode does not allow whitespace".to_owned())
+                return Err(TildeError::golf_only("golf-only mode does not allow whitespace", Some(span_to_here(&rev_tokens))))
             }
             //tilde_log!("skipping whitespace");
+        } else if current == '{' || current == '}' {
+            if mode.golf_only() {
+                return Err(TildeError::golf_only(format!("golf-only mode does not allow long-mode block delimiters ({current})"), Some(span_to_here(&rev_tokens))))
+            }
+            if current == '{' {
+                open_block(&mut open_blocks, &mut ops, &mut spans, start);
+            } else {
+                close_block(&mut open_blocks, &mut ops, &mut spans, span_to_here(&rev_tokens))?;
+            }
+        } else if current == '=' {
+            if mode.golf_only() {
+                return Err(TildeError::golf_only("golf-only mode does not allow named (long-mode) variables (=)", Some(span_to_here(&rev_tokens))))
+            }
+            string_buffer.clear();
+            while let Some(token) = rev_tokens.pop() {
+                if !token.is_alphabetic() && token != '-' {
+                    rev_tokens.push(token);
+                    break;
+                }
+                string_buffer.push(token)
+            }
+            tilde_log!("store variable (long mode): \"{}\"", &string_buffer);
+            if string_buffer.is_empty() {
+                return Err(TildeError::parse("expected a variable name after '='", span_to_here(&rev_tokens)))
+            }
+            if lookup_op_long(&string_buffer).is_some() {
+                return Err(TildeError::parse(format!("cannot use operation name '{}' as variable", &string_buffer), span_to_here(&rev_tokens)))
+            }
+            stored_names.insert(string_buffer.clone());
+            ops.push(Store::new(string_buffer.clone()));
+            spans.push(span_to_here(&rev_tokens))
         } else if current == ',' || current == '\'' {
             if mode.golf_only() {
-                return Err(format!("golf-only mode does not allow plain-text (long-mode) string literals ({current})"))
+                return Err(TildeError::golf_only(format!("golf-only mode does not allow plain-text (long-mode) string literals ({current})"), Some(span_to_here(&rev_tokens))))
             }
             string_buffer.clear();
             while let Some(token) = rev_tokens.pop() {
@@ -39,10 +85,11 @@ pub fn parse(src: &str, mode: RunMode) -> TildeRes<Prog> {
             }
             tilde_log!("string literal (long mode): '{}'", &string_buffer);
             let op = TextOp::new(string_buffer.clone());
-            ops.push(op)
+            ops.push(op);
+            spans.push(span_to_here(&rev_tokens))
         } else if ('1'..='9').contains(&current) || current == '.' || current == '-' {
             if mode.golf_only() {
-                return Err(format!("golf-only mode does not allow decimal integer literals string literals ({current})"))
+                return Err(TildeError::golf_only(format!("golf-only mode does not allow decimal integer literals string literals ({current})"), Some(span_to_here(&rev_tokens))))
             }
             // note that short-mode numbers start with 0, long-mode ones cannot
             string_buffer.clear();
@@ -58,12 +105,13 @@ pub fn parse(src: &str, mode: RunMode) -> TildeRes<Prog> {
             let op = NumberOp::new(
                 string_buffer
                     .parse::<Nr>()
-                    .map_err(|err| format!("invalid number '{string_buffer}', err {err}"))?,
+                    .map_err(|err| TildeError::parse(format!("invalid number '{string_buffer}', err {err}"), span_to_here(&rev_tokens)))?,
             );
-            ops.push(op)
+            ops.push(op);
+            spans.push(span_to_here(&rev_tokens))
         } else if current.is_alphabetic() || current == '-' {
             if mode.golf_only() {
-                return Err(format!("golf-only mode does not allow alphanumeric (long-mode) operator names ({current})"))
+                return Err(TildeError::golf_only(format!("golf-only mode does not allow alphanumeric (long-mode) operator names ({current})"), Some(span_to_here(&rev_tokens))))
             }
             string_buffer.clear();
             string_buffer.push(current);
@@ -77,23 +125,34 @@ pub fn parse(src: &str, mode: RunMode) -> TildeRes<Prog> {
                 string_buffer.push(token)
             }
             tilde_log!("operator by long name: \"{}\"", &string_buffer);
-            let op = lookup_op_long(&string_buffer).ok_or_else(|| {
+            let op = lookup_op_long(&string_buffer).or_else(|| {
+                if is_register(&string_buffer) || stored_names.contains(&string_buffer) {
+                    tilde_log!("load variable (long mode): \"{}\"", &string_buffer);
+                    return Some(Load::new(string_buffer.clone()))
+                }
+                None
+            }).ok_or_else(|| {
                 tilde_log!("did not find long '{}', make sure it is in `all_non_literals`", &string_buffer);
-                format!("could not find an identifier by name '{}'", &string_buffer)
+                TildeError::parse(format!("could not find an identifier by name '{}'", &string_buffer), span_to_here(&rev_tokens))
             })?;
-            ops.push(op)
+            ops.push(op);
+            spans.push(span_to_here(&rev_tokens))
         } else if let Some(golf_letter) = Letter::from_symbol(current) {
             let mut word = GolfWordContent::new();
             word.push(golf_letter);
             match golf_letter.kind() {
-                LetterKind::Literal => unreachable!(),
+                LetterKind::Literal => return Err(TildeError::golf_only(
+                    format!("golfed number literals are not supported yet ({current})"), Some(span_to_here(&rev_tokens)))),
                 LetterKind::VariableOpen => {},
                 LetterKind::FixedOpen => match rev_tokens.pop().map(Letter::from_symbol) {
                     Some(Some(second)) => word.push(second),
-                    Some(None) => todo!("handle golf fixed opener expected next to be golf letter"),  //TODO @mark:
-                    None => todo!("handle unexpected end"),  //TODO @mark:
+                    Some(None) => return Err(TildeError::parse(
+                        format!("golf letter {current} must be followed by another golf letter"), span_to_here(&rev_tokens))),
+                    None => return Err(TildeError::parse(
+                        format!("unexpected end of source after golf letter {current}, which must be followed by another letter"), span_to_here(&rev_tokens))),
                 },
-                LetterKind::Modifier => unimplemented!("cannot start with modifier"),
+                LetterKind::Modifier => return Err(TildeError::parse(
+                    format!("golf modifier {current} cannot appear without preceding opener"), span_to_here(&rev_tokens))),
             }
             while let Some(chr) = rev_tokens.pop() {
                 if let Some(letter) = Letter::from_symbol(chr) {
@@ -106,12 +165,21 @@ pub fn parse(src: &str, mode: RunMode) -> TildeRes<Prog> {
                 break
             }
             tilde_log!("operator by short name: \"{}\"", &word);
-            let op = lookup_op_golf(&GolfWord::new(word.clone())).ok_or_else(|| {
-                //TODO @mark: get rid of clone? ^
+            let golf_word = GolfWord::new(word.clone());
+            if golf_word == BlockOp::golf_open() {
+                open_block(&mut open_blocks, &mut ops, &mut spans, start);
+                continue
+            }
+            if golf_word == BlockOp::golf_close() {
+                close_block(&mut open_blocks, &mut ops, &mut spans, span_to_here(&rev_tokens))?;
+                continue
+            }
+            let op = lookup_op_golf(&golf_word).or_else(|| lookup_register_golf(&golf_word)).ok_or_else(|| {
                 tilde_log!("did not find short '{}', make sure it is in `all_non_literals`", &word);
-                format!("could not find golf code '{}'", &word)
+                TildeError::parse(format!("could not find golf code '{}'", &word), span_to_here(&rev_tokens))
             })?;
-            ops.push(op)
+            ops.push(op);
+            spans.push(span_to_here(&rev_tokens))
         } else if current == '"' {
             //TODO @mark: make more resistant to closer changes?
             letters_buffer.clear();
@@ -124,13 +192,36 @@ pub fn parse(src: &str, mode: RunMode) -> TildeRes<Prog> {
             tilde_log!("string lookup (short mode), {} golf letters", letters_buffer.len());
             string_buffer.clear();
             let str_res = decode_str(&letters_buffer, &mut string_buffer, &mut string_decode_buffer)
-                .map_err(|err| format!("could not parse golfed string, err: {err}"))?;
-            ops.push(TextOp::new(string_buffer.clone()))
+                .map_err(|err| TildeError::decode(err, span_to_here(&rev_tokens)))?;
+            ops.push(TextOp::new(string_buffer.clone()));
+            spans.push(span_to_here(&rev_tokens))
         } else {
-            return Err(format!("unrecognized: {current}"))
+            return Err(TildeError::parse(format!("unrecognized: {current}"), span_to_here(&rev_tokens)))
         }
     }
-    Ok(Prog::of(ops))
+    // blocks that are still open are closed implicitly at the end
+    while !open_blocks.is_empty() {
+        close_block(&mut open_blocks, &mut ops, &mut spans, Span::new(token_cnt, token_cnt))?;
+    }
+    Ok(Prog::with_spans(ops, spans))
+}
+
+/// Start collecting operations for a block, keeping the enclosing ones and where the block started.
+fn open_block(open_blocks: &mut Vec<(Vec<Op>, Vec<Span>, usize)>, ops: &mut Vec<Op>, spans: &mut Vec<Span>, start: usize) {
+    tilde_log!("opening block");
+    open_blocks.push((mem::take(ops), mem::take(spans), start));
+}
+
+/// Turn the operations collected since the matching opener into a block in the enclosing operations.
+fn close_block(open_blocks: &mut Vec<(Vec<Op>, Vec<Span>, usize)>, ops: &mut Vec<Op>, spans: &mut Vec<Span>, closer: Span) -> TildeRes<()> {
+    let Some((outer_ops, outer_spans, start)) = open_blocks.pop() else {
+        return Err(TildeError::parse("block closer without matching opener", closer))
+    };
+    tilde_log!("closing block of {} operations", ops.len());
+    let block = BlockOp::new(Prog::with_spans(mem::replace(ops, outer_ops), mem::replace(spans, outer_spans)));
+    ops.push(block);
+    spans.push(Span::new(start, closer.end));
+    Ok(())
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -141,6 +232,7 @@ pub struct Pos<T: Debug + PartialEq> {
 
 #[cfg(test)]
 mod tests {
+    use crate::ErrorKind;
     use crate::op::Div;
     use crate::op::Drop;
     use crate::op::Duplicate;
@@ -222,6 +314,20 @@ mod tests {
         assert!(parse("unknown-operator", RunMode::Any).is_err());
     }
 
+    #[test]
+    fn unknown_operator_span() {
+        let err = parse("dup  unknown-operator 1", RunMode::Any).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Parse(_)));
+        assert_eq!(err.span(), Some(Span::new(5, 21)));
+    }
+
+    #[test]
+    fn golf_only_span() {
+        let err = parse("+/ 1", RunMode::GolfOnly).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::GolfOnly(_)));
+        assert_eq!(err.span(), Some(Span::new(2, 3)));
+    }
+
     #[test]
     fn operator_by_gold() {
         assert_eq!(parse_any("/"), of(Drop::new()));
@@ -243,5 +349,63 @@ mod tests {
         assert_eq!(parse_any("div\n1"), Prog::of(vec![Div::new(), NumberOp::new(1.0)]),);
     }
 
+    #[test]
+    fn block_nested() {
+        let inner = Prog::of(vec![Duplicate::new()]);
+        let outer = Prog::of(vec![NumberOp::new(1.0), BlockOp::new(inner), Drop::new()]);
+        assert_eq!(parse_any("{1 {dup} drop}"), of(BlockOp::new(outer)));
+        assert_eq!(parse_any("{ }"), of(BlockOp::new(Prog::of(vec![]))));
+    }
+
+    #[test]
+    fn block_implicit_close() {
+        let inner = Prog::of(vec![Duplicate::new()]);
+        let outer = Prog::of(vec![BlockOp::new(inner)]);
+        assert_eq!(parse_any("{ { dup"), of(BlockOp::new(outer)));
+    }
+
+    #[test]
+    fn block_unmatched_close() {
+        let err = parse("{ dup } }", RunMode::Any).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Parse(_)));
+        assert_eq!(err.span(), Some(Span::new(8, 9)));
+        let err = parse("%[/%>%>", RunMode::Any).unwrap_err();
+        assert_eq!(err.span(), Some(Span::new(5, 7)));
+    }
+
+    #[test]
+    fn block_golf_only() {
+        assert!(matches!(parse("{/}", RunMode::GolfOnly).unwrap_err().kind(), ErrorKind::GolfOnly(_)));
+        let expected = of(BlockOp::new(Prog::of(vec![Drop::new()])));
+        assert_eq!(parse("%[/%>", RunMode::GolfOnly).unwrap(), expected);
+    }
+
+    #[test]
+    fn block_round_trip() {
+        let prog = parse_any("{ drop { drop } } drop { { drop } }");
+        assert_eq!(prog.long_code(), "{ drop { drop } } drop { { drop } }");
+        assert_eq!(parse_any(&prog.long_code()), prog);
+        let golf = prog.golf_code().unwrap();
+        assert_eq!(golf, "%[/%[/%>%>/%[%[/");
+        assert_eq!(parse(&golf, RunMode::GolfOnly).unwrap(), prog);
+    }
+
+    #[test]
+    fn variables_long() {
+        let expected = Prog::of(vec![Store::new("x"), Load::new("x"), Load::new("a")]);
+        assert_eq!(parse_any("=x x a"), expected);
+        assert!(matches!(parse("x =x", RunMode::Any).unwrap_err().kind(), ErrorKind::Parse(_)));
+        assert!(matches!(parse("=drop", RunMode::Any).unwrap_err().kind(), ErrorKind::Parse(_)));
+        assert!(matches!(parse("= x", RunMode::Any).unwrap_err().kind(), ErrorKind::Parse(_)));
+    }
+
+    #[test]
+    fn registers_golf() {
+        let prog = parse_any("=a drop =e b");
+        assert_eq!(prog.golf_code().unwrap(), "&^/&~$!");
+        assert_eq!(parse("&^/&~$!", RunMode::GolfOnly).unwrap(), prog);
+        assert!(parse_any("=x").golf_code().is_err());
+    }
+
     //TODO @mark: add some golfed testcases to existing tests, like whitespace ones
 }
@@ -1,40 +1,70 @@
 use ::std::fmt::Write;
+use ::std::hash;
 use ::std::ops::Index;
 pub use ::std::slice;
 
 use crate::common::b64_encode;
+use crate::common::Span;
 use crate::compile::Letter;
+use crate::op::BlockOp;
 use crate::op::Op;
+use crate::TildeError;
 use crate::TildeRes;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Prog {
     ops: Vec<Op>,
+    /// Position in the source of each op, if the program was parsed from source.
+    spans: Vec<Span>,
 }
 
 impl Prog {
     pub fn of(ops: Vec<Op>) -> Self {
-        Prog { ops }
+        Prog { ops, spans: vec![] }
+    }
+
+    pub fn with_spans(ops: Vec<Op>, spans: Vec<Span>) -> Self {
+        debug_assert_eq!(ops.len(), spans.len());
+        Prog { ops, spans }
     }
 
     pub fn get(&self, index: usize) -> Option<&Op> {
         self.ops.get(index)
     }
 
+    pub fn span(&self, index: usize) -> Option<Span> {
+        self.spans.get(index).copied()
+    }
+
     pub fn len(&self) -> usize {
         self.ops.len()
     }
 
-    // pub fn iter(&self) -> slice::Iter<Op> {
-    //     self.ops.iter()
-    // }
-    //
+    pub fn iter(&self) -> slice::Iter<Op> {
+        self.ops.iter()
+    }
+
     // pub fn into_iter(self) -> vec::IntoIter<Op> {
     //     self.ops.into_iter()
     // }
     //TODO @mverleg:
 }
 
+/// Programs are equal if their operations are, regardless of source positions.
+impl PartialEq for Prog {
+    fn eq(&self, other: &Self) -> bool {
+        self.ops == other.ops
+    }
+}
+
+impl Eq for Prog {}
+
+impl hash::Hash for Prog {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.ops.hash(state)
+    }
+}
+
 impl Index<usize> for Prog {
     type Output = Op;
 
@@ -56,16 +86,32 @@ impl Prog {
     fn golf_letters(&self) -> TildeRes<Vec<Letter>> {
         //TODO @mark: cache?
         let mut letters = Vec::with_capacity(self.ops.len() * 4);
-        for op in &self.ops {
+        self.push_golf_letters(&mut letters, true)?;
+        Ok(letters)
+    }
+
+    /// Blocks are encoded as an opening word, their operations and a closing word. Closers at the end
+    /// of the program are implicit, so `is_end` is used to leave them out.
+    fn push_golf_letters(&self, letters: &mut Vec<Letter>, is_end: bool) -> TildeRes<()> {
+        for (i, op) in self.ops.iter().enumerate() {
+            let is_last = is_end && i + 1 == self.ops.len();
+            if let Some(block) = op.as_any().downcast_ref::<BlockOp>() {
+                letters.extend(BlockOp::golf_open());
+                block.prog().push_golf_letters(letters, is_last)?;
+                if !is_last {
+                    letters.extend(BlockOp::golf_close());
+                }
+                continue
+            }
             let word = op.golf_code()
-                .ok_or_else(|| format!("operation {:?} has no golf representation", op))?;
+                .ok_or_else(|| TildeError::golf_only(format!("operation {:?} has no golf representation", op), self.span(i)))?;
             letters.extend(word);
+            //TODO @mark: make a minify function with more tricks
+            if is_last && letters.last() == Some(&Letter::Text) {
+                letters.pop();
+            }
         }
-        //TODO @mark: make a minify function with more tricks
-        if letters.last() == Some(&Letter::Text) {
-            letters.pop();
-        }
-        Ok(letters)
+        Ok(())
     }
 
     pub fn golf_len(&self) -> TildeRes<usize> {
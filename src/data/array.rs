@@ -1,24 +1,48 @@
+use ::std::cell::RefCell;
 use ::std::fmt;
+use ::std::hash;
 use ::std::rc::Rc;
-use ::std::slice::Iter;
-use ::std::vec;
 
 use crate::data::fork::Fork;
 use crate::data::value::Value;
+use crate::exec::has_failed;
+use crate::exec::take_element;
+use crate::exec::track_size;
 use crate::Nr;
+use crate::TildeError;
+use crate::TildeRes;
 
-#[derive(PartialEq, Eq, Hash)]
 pub struct Array {
-    val: Rc<Vec<Value>>,
+    val: Rc<RefCell<ArrayData>>,
+}
+
+struct ArrayData {
+    items: Vec<Value>,
+    /// Produces the remaining elements on demand, for arrays backed by a stream like stdin.
+    rest: Option<Box<dyn Iterator<Item=Value>>>,
 }
 
 impl Array {
     pub fn of<V: Into<Value>>(vec: Vec<V>) -> Self {
+        track_size(vec.len());
+        Array {
+            val: Rc::new(RefCell::new(ArrayData {
+                items: vec
+                    .into_iter()
+                    .map(|v| v.into())
+                    .collect(),
+                rest: None,
+            }))
+        }
+    }
+
+    /// Array whose elements are only produced when they are needed. Forks share the elements.
+    pub fn lazy(rest: impl Iterator<Item=Value> + 'static) -> Self {
         Array {
-            val: Rc::new(vec
-                .into_iter()
-                .map(|v| v.into())
-                .collect())
+            val: Rc::new(RefCell::new(ArrayData {
+                items: Vec::new(),
+                rest: Some(Box::new(rest)),
+            }))
         }
     }
 
@@ -41,29 +65,79 @@ impl Array {
     //     &mut self.val
     // }
 
-    pub fn index(&self, nr: Nr) -> Value {
-        let ix: usize = nr.floor().try_into().expect("value to large to use as index");
-        match self.val.get(ix) {
-            Some(val) => {
-                val.fork()
-            },
-            None => Value::default(),
-        }
+    /// Element at the index, or the default value if the index is past the end.
+    /// Fails if the index is not a non-negative whole number.
+    pub fn index(&self, nr: Nr) -> TildeRes<Value> {
+        let ix = usize::try_from(nr.to_uint()?)
+            .map_err(|_| TildeError::runtime(format!("index {nr} is too large")))?;
+        Ok(self.get(ix).unwrap_or_default())
+    }
+
+    /// Element at the index, producing only the elements up to it if the array is lazy.
+    pub fn get(&self, ix: usize) -> Option<Value> {
+        self.produce_until(ix + 1);
+        self.val.borrow().items.get(ix).map(|val| val.fork())
     }
 
-    pub fn iter(&self) -> Iter<'_, Value> {
-        self.val.iter()
+    pub fn iter(&self) -> ArrayIter {
+        ArrayIter { arr: self.fork(), ix: 0 }
     }
 
     pub fn len(&self) -> usize {
-        self.val.len()
+        self.produce_until(usize::MAX);
+        self.val.borrow().items.len()
+    }
+
+    /// Whether all elements have been produced, which is always the case if the array is not lazy.
+    pub fn is_complete(&self) -> bool {
+        self.val.borrow().rest.is_none()
     }
 
     pub fn tail(&self) -> (Array, Value) {
-        let mut new = (*self.val).fork();
+        self.produce_until(usize::MAX);
+        let mut new = self.val.borrow().items.fork();
         let last = new.pop().unwrap_or_else(Value::default);
         (Array::of(new), last)
     }
+
+    /// Produce lazy elements until there are `len`, or fewer if the array ends or the execution
+    /// budget runs out. In the latter case the array stays incomplete and the error is reported later.
+    fn produce_until(&self, len: usize) {
+        let mut data = self.val.borrow_mut();
+        while data.items.len() < len {
+            let Some(rest) = data.rest.as_mut() else {
+                return
+            };
+            if has_failed() {
+                return
+            }
+            match rest.next() {
+                Some(val) => {
+                    data.items.push(val);
+                    if !take_element() {
+                        return
+                    }
+                },
+                None => data.rest = None,
+            }
+        }
+    }
+}
+
+/// Iterates over forks of the elements, producing them one by one if the array is lazy.
+pub struct ArrayIter {
+    arr: Array,
+    ix: usize,
+}
+
+impl Iterator for ArrayIter {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        let val = self.arr.get(self.ix)?;
+        self.ix += 1;
+        Some(val)
+    }
 }
 
 impl Fork for Array {
@@ -73,11 +147,26 @@ impl Fork for Array {
     }
 }
 
+impl PartialEq for Array {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.val, &other.val) || self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for Array {}
+
+impl hash::Hash for Array {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.produce_until(usize::MAX);
+        self.val.borrow().items.hash(state)
+    }
+}
+
 impl fmt::Display for Array {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "[")?;
         let mut is_first = true;
-        for item in &*self.val {
+        for item in self.iter() {
             if is_first {
                 is_first = false;
             } else {
@@ -91,10 +180,12 @@ impl fmt::Display for Array {
 }
 
 impl fmt::Debug for Array {
+    /// Unlike [fmt::Display], this does not produce the remaining elements of lazy arrays.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = self.val.borrow();
         write!(f, "[")?;
         let mut is_first = true;
-        for item in &*self.val {
+        for item in &data.items {
             if is_first {
                 is_first = false;
             } else {
@@ -102,6 +193,12 @@ impl fmt::Debug for Array {
             }
             write!(f, "{:?}", item)?;
         }
+        if data.rest.is_some() {
+            if !is_first {
+                write!(f, ",")?;
+            }
+            write!(f, "...")?;
+        }
         write!(f, "]")?;
         Ok(())
     }
@@ -147,4 +244,36 @@ mod tests {
         let arr = Array::of(vec![Value::Num(Nr::new(2.718)), Value::Txt(Text::of("hello"))]);
         assert_eq!(format!("{:?}", arr), "[2.718,\"hello\"]")
     }
+
+    #[test]
+    fn index_must_be_whole() {
+        let arr = Array::of(vec![Nr::new(1.0), Nr::new(2.0)]);
+        assert_eq!(arr.index(Nr::new(5.0)).unwrap(), Value::default());
+        assert!(arr.index(Nr::new(-1.0)).is_err());
+        assert!(arr.index(Nr::new(0.5)).is_err());
+    }
+
+    #[test]
+    fn lazy_produces_on_demand() {
+        let mut produced = 0;
+        let arr = Array::lazy((1..=3).map(move |nr| {
+            produced += 1;
+            assert!(produced <= nr);
+            Value::Num(Nr::from(nr as u32))
+        }));
+        assert_eq!(format!("{:?}", arr), "[...]");
+        assert_eq!(arr.index(Nr::new(1.0)).unwrap(), Value::Num(Nr::new(2.0)));
+        assert_eq!(format!("{:?}", arr), "[1,2,...]");
+        assert!(!arr.is_complete());
+        assert_eq!(arr.len(), 3);
+        assert!(arr.is_complete());
+        assert_eq!(format!("{:?}", arr), "[1,2,3]");
+    }
+
+    #[test]
+    fn lazy_equals_eager() {
+        let lazy = Array::lazy(vec![Value::Txt(Text::of("a")), Value::Txt(Text::of("b"))].into_iter());
+        assert_eq!(lazy.fork(), Array::of(vec![Text::of("a"), Text::of("b")]));
+        assert_eq!(format!("{}", lazy), "[a,b]");
+    }
 }
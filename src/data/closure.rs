@@ -1,130 +1,163 @@
 use ::std::fmt;
 use std::rc::Rc;
+use crate::compile::Prog;
 use crate::data::Fork;
 
-use crate::exec::{dispatch_binary, dispatch_unary, Executor, new_small_stack};
+use crate::exec::{dispatch_op, enter_closure, new_small_stack, take_step, trace_before, trace_op};
 use crate::exec::Stack;
 use crate::op::Op;
 use crate::{tilde_log, Value};
+use crate::TildeRes;
 use crate::Values;
 
-/// Which type of capture
+/// One operation in a closure, together with the values it captured from outside the closure.
+/// When run, it takes `free` values from the top of the closure's stack, puts the captured values
+/// around them, and runs the operation on that.
 #[derive(Debug, PartialEq, Eq, Hash)]
-pub enum CaptureType {
-    Unary(Op),
-    BinaryFreeDeep(Op, Value),
-    BinaryFreeTop(Op, Value),
-    TernaryFreeDeep(Op, Value, Value),
-    TernaryFreeMiddle(Op, Value, Value),
-    TernaryFreeTop(Op, Value, Value),
+struct Capture {
+    op: Op,
+    /// Captured values that go below the closure's values, deepest first.
+    deep: Vec<Value>,
+    /// How many of the operation's inputs come from the closure's own stack.
+    free: usize,
+    /// Captured values that go above the closure's values, deepest first.
+    top: Vec<Value>,
 }
 
-impl Fork for CaptureType {
+impl Fork for Capture {
     fn fork(&self) -> Self {
-        match self {
-            CaptureType::Unary(op) => CaptureType::Unary(op.clone()),
-            CaptureType::BinaryFreeDeep(op, top) => CaptureType::BinaryFreeDeep(op.clone(), top.fork()),
-            CaptureType::BinaryFreeTop(op, deep) => CaptureType::BinaryFreeTop(op.clone(), deep.fork()),
-            CaptureType::TernaryFreeDeep(op, middle, top) => CaptureType::TernaryFreeDeep(op.clone(), middle.fork(), top.fork()),
-            CaptureType::TernaryFreeMiddle(op, deep, top) => CaptureType::TernaryFreeMiddle(op.clone(), deep.fork(), top.fork()),
-            CaptureType::TernaryFreeTop(op, deep, middle) => CaptureType::TernaryFreeTop(op.clone(), deep.fork(), middle.fork()),
+        Capture {
+            op: self.op.clone(),
+            deep: self.deep.fork(),
+            free: self.free,
+            top: self.top.fork(),
         }
     }
 }
 
+/// A closure is a sub-program that runs on a stack starting with just its argument. It keeps track
+/// of how many values it leaves on that stack, so that operations that are added to it take the right
+/// number of values from the closure, and only capture the remaining ones from outside.
 #[derive(PartialEq, Eq, Hash)]
 pub struct Func {
-    items: Rc<Vec<CaptureType>>,
+    items: Rc<Vec<Capture>>,
+    /// How many values the closure leaves on its stack, based on the operations' output counts.
+    out_count: usize,
 }
 
 impl Func {
     pub fn new() -> Self {
-        Func { items: Rc::new(Vec::with_capacity(4)) }
+        Func { items: Rc::new(Vec::with_capacity(4)), out_count: 1 }
     }
 
-    pub fn run_on_single(&self, initial_stack_value: Value) -> Values {
+    /// Closure that runs the operations of the program on its argument, without capturing anything.
+    pub fn of_prog(prog: &Prog) -> Self {
+        let mut items = Vec::with_capacity(prog.len());
+        let mut out_count = 1usize;
+        for op in prog.iter() {
+            let free = op.as_executor().input_count();
+            out_count = out_count.saturating_sub(free) + op.output_count();
+            items.push(Capture { op: op.clone(), deep: vec![], free, top: vec![] });
+        }
+        Func { items: Rc::new(items), out_count }
+    }
+
+    /// Whether all captured values are fully produced, see [Value::is_produced].
+    pub fn is_produced(&self) -> bool {
+        self.items.iter()
+            .all(|capture| capture.deep.iter().chain(&capture.top).all(Value::is_produced))
+    }
+
+    pub fn run_on_single(&self, initial_stack_value: Value) -> TildeRes<Values> {
         let mut stack = new_small_stack();
         stack.push(initial_stack_value);
-        self.run_on_stack(&mut stack);
-        stack
+        self.run_on_stack(&mut stack)?;
+        Ok(stack)
     }
 
-    pub fn run_on_stack(&self, stack: &mut impl Stack) {
+    pub fn run_on_stack(&self, stack: &mut impl Stack) -> TildeRes<()> {
         tilde_log!("using stack {:?} to run closure {:?}", stack, self);
-        for cap in &*self.items {
-            let free_value = stack.pop();
-            let res = match cap {
-                CaptureType::Unary(op) => {
-                    let Executor::Unary(ex) = op.as_executor() else {
-                        unreachable!();  //TODO @mark: really?
-                    };
-                    dispatch_unary(ex, free_value)
-                },
-                CaptureType::BinaryFreeDeep(op, top) => {
-                    let Executor::Binary(ex) = op.as_executor() else {
-                        unreachable!();  //TODO @mark: really?
-                    };
-                    dispatch_binary(ex, Some(top.fork()), free_value)
-                    //TODO @mark: get rid of top clone
-                }
-                CaptureType::BinaryFreeTop(op, deep) => {
-                    let Executor::Binary(ex) = op.as_executor() else {
-                        unreachable!();  //TODO @mark: really?
-                    };
-                    dispatch_binary(ex, free_value, Some(deep.fork()))
-                    //TODO @mark: get rid of deep clone
+        let _trace = enter_closure();
+        for (i, cap) in self.items.iter().enumerate() {
+            take_step()?;
+            let before = trace_before(stack);
+            let mut free_values = Values::new();
+            for _ in 0..cap.free {
+                match stack.pop() {
+                    Some(value) => free_values.push(value),
+                    None => break,
                 }
-                CaptureType::TernaryFreeDeep(op, _, _) => todo!(),
-                CaptureType::TernaryFreeMiddle(op, _, _) => todo!(),
-                CaptureType::TernaryFreeTop(op, _, _) => todo!(),
-            };
-            stack.push_all(res)
+            }
+            free_values.reverse();
+            for value in &cap.deep {
+                stack.push(value.fork());
+            }
+            stack.push_all(free_values);
+            for value in &cap.top {
+                stack.push(value.fork());
+            }
+            let res = dispatch_op(stack, &cap.op)?;
+            stack.push_all(res);
+            trace_op(i, &cap.op, before, stack);
         }
+        Ok(())
     }
 
     pub fn with_unary(self, op: Op) -> Self {
-        let mut new = self.fork_content();
-        //TODO @mark: prevent clone (and sibling methods)
-        new.push(CaptureType::Unary(op));
-        Func { items: Rc::new(new) }
+        self.with_op(op, vec![], vec![]).1
     }
 
     pub fn with_bin_deep(self, op: Op, top: Value) -> Self {
-        let mut new = self.fork_content();
-        new.push(CaptureType::BinaryFreeDeep(op, top));
-        Func { items: Rc::new(new) }
+        self.with_op(op, vec![], vec![top]).1
     }
 
-    pub fn with_bin_top(self, op: Op, deep: Value) -> Self {
-        let mut new = self.fork_content();
-        new.push(CaptureType::BinaryFreeTop(op, deep));
-        Func { items: Rc::new(new) }
+    /// Returns the new closure, preceded by `deep` if the closure provides both inputs.
+    pub fn with_bin_top(self, op: Op, deep: Value) -> Values {
+        self.with_op_values(op, vec![deep], vec![])
     }
 
     pub fn with_tern_deep(self, op: Op, middle: Value, top: Value) -> Self {
-        let mut new = self.fork_content();
-        new.push(CaptureType::TernaryFreeDeep(op, middle, top));
-        Func { items: Rc::new(new) }
+        self.with_op(op, vec![], vec![middle, top]).1
     }
 
-    pub fn with_tern_middle(self, op: Op, deep: Value, top: Value) -> Self {
-        let mut new = self.fork_content();
-        new.push(CaptureType::TernaryFreeMiddle(op, deep, top));
-        Func { items: Rc::new(new) }
+    /// Returns the new closure, preceded by `deep` if the closure provides the deep input.
+    pub fn with_tern_middle(self, op: Op, deep: Value, top: Value) -> Values {
+        self.with_op_values(op, vec![deep], vec![top])
+    }
+
+    /// Returns the new closure, preceded by any of `deep` and `middle` that the closure provides itself.
+    pub fn with_tern_top(self, op: Op, deep: Value, middle: Value) -> Values {
+        self.with_op_values(op, vec![deep, middle], vec![])
+    }
+
+    fn with_op_values(self, op: Op, deep: Vec<Value>, top: Vec<Value>) -> Values {
+        let (unused, func) = self.with_op(op, deep, top);
+        let mut values = Values::from_iter(unused);
+        values.push(Value::Func(func));
+        values
     }
 
-    pub fn with_tern_top(self, op: Op, deep: Value, middle: Value) -> Self {
+    /// Add an operation, for which the closure is the input after `deep` and before `top`. The inputs
+    /// at and below the closure are taken from the closure's own values where possible, and captured
+    /// from `deep` otherwise. Any values from `deep` that are not needed are returned, deepest first.
+    fn with_op(self, op: Op, mut deep: Vec<Value>, top: Vec<Value>) -> (Vec<Value>, Self) {
+        let wanted = deep.len() + 1;
+        let free = self.out_count.clamp(1, wanted);
+        // the closure provides `free` inputs, so only the topmost values of `deep` that are still needed are captured
+        let captured = deep.split_off(free - 1);
+        let out_count = self.out_count.saturating_sub(free) + op.output_count();
         let mut new = self.fork_content();
-        new.push(CaptureType::TernaryFreeTop(op, deep, middle));
-        Func { items: Rc::new(new) }
+        //TODO @mark: prevent clone
+        new.push(Capture { op, deep: captured, free, top });
+        (deep, Func { items: Rc::new(new), out_count })
     }
 
-    fn fork_content(&self) -> Vec<CaptureType> {
+    fn fork_content(&self) -> Vec<Capture> {
         self.items.iter().map(|item| item.fork()).collect()
     }
 
     pub fn fork(&self) -> Func {
-        Func { items: self.items.clone() }
+        Func { items: self.items.clone(), out_count: self.out_count }
     }
 }
 
@@ -144,16 +177,75 @@ impl fmt::Debug for Func {
             } else {
                 write!(f, " ")?;
             }
-            match item {
-                CaptureType::Unary(op) => write!(f, "_ {op:?}")?,
-                CaptureType::BinaryFreeDeep(op, top) => write!(f, "(_ {top:?} {op:?})")?,
-                CaptureType::BinaryFreeTop(op, deep) => write!(f, "({deep:?} _ {op:?})")?,
-                CaptureType::TernaryFreeDeep(op, middle, top) => write!(f, "(_ {middle:?} {top:?} {op:?})")?,
-                CaptureType::TernaryFreeMiddle(op, deep, top) => write!(f, "({deep:?} _ {top:?} {op:?})")?,
-                CaptureType::TernaryFreeTop(op, deep, middle) => write!(f, "({deep:?} {middle:?} _ {op:?})")?,
+            if item.deep.is_empty() && item.top.is_empty() && item.free == 1 {
+                write!(f, "_ {:?}", item.op)?;
+                continue
+            }
+            write!(f, "(")?;
+            for value in &item.deep {
+                write!(f, "{value:?} ")?;
             }
+            for _ in 0..item.free {
+                write!(f, "_ ")?;
+            }
+            for value in &item.top {
+                write!(f, "{value:?} ")?;
+            }
+            write!(f, "{:?})", item.op)?;
         }
         write!(f, ")")?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::compile::parse;
+    use crate::op::Duplicate;
+    use crate::op::Minus;
+    use crate::op::Mul;
+    use crate::op::testing::nr;
+    use crate::RunMode;
+    use crate::values;
+
+    use super::*;
+
+    fn single_func(mut values: Values) -> Func {
+        assert_eq!(values.len(), 1);
+        match values.pop() {
+            Some(Value::Func(func)) => func,
+            other => panic!("expected function, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn capture_outside_value() {
+        let func = single_func(Func::new().with_bin_top(Minus::new(), nr(10.0)));
+        assert_eq!(func.run_on_single(nr(3.0)).unwrap(), values![nr(7.0)]);
+        let func = Func::new().with_bin_deep(Minus::new(), nr(10.0));
+        assert_eq!(func.run_on_single(nr(3.0)).unwrap(), values![nr(-7.0)]);
+    }
+
+    #[test]
+    fn multiple_outputs_are_used_before_capturing() {
+        let mut res = Func::new().with_unary(Duplicate::new()).with_bin_top(Mul::new(), nr(10.0));
+        let Some(Value::Func(func)) = res.pop() else {
+            panic!("expected function")
+        };
+        assert_eq!(res, values![nr(10.0)]);
+        assert_eq!(func.run_on_single(nr(3.0)).unwrap(), values![nr(9.0)]);
+        let func = single_func(func.with_bin_top(Minus::new(), nr(10.0)));
+        assert_eq!(func.run_on_single(nr(3.0)).unwrap(), values![nr(1.0)]);
+    }
+
+    #[test]
+    fn closure_from_program() {
+        let func = Func::of_prog(&parse("dup mul 1", RunMode::Any).unwrap());
+        assert_eq!(func.run_on_single(nr(3.0)).unwrap(), values![nr(9.0), nr(1.0)]);
+        let mut res = func.with_bin_top(Minus::new(), nr(10.0));
+        let Some(Value::Func(func)) = res.pop() else {
+            panic!("expected function")
+        };
+        assert_eq!(res, values![nr(10.0)]);
+        assert_eq!(func.run_on_single(nr(3.0)).unwrap(), values![nr(8.0)]);
+    }
+}
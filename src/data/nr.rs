@@ -6,6 +6,8 @@ use ::std::str::FromStr;
 use std::fmt::Formatter;
 use crate::data::Fork;
 
+use crate::TildeError;
+use crate::TildeRes;
 use crate::UINT;
 
 #[derive(Clone, Copy)]
@@ -21,7 +23,8 @@ impl Nr {
         if value == f64::NEG_INFINITY {
             return Nr { value: f64::MIN }
         }
-        if value.is_nan() {
+        if value.is_nan() || value == 0.0 {
+            // also turns -0 into 0, so it is not shown as '-0' and hashes like 0
             return Nr { value: 0.0 }
         }
         Nr { value }
@@ -47,14 +50,91 @@ impl Nr {
         Nr::new(self.value / other.value)
     }
 
-    pub fn floor(&self) -> UINT {
-        self.value as UINT
-        //TODO @mark: no try-into, why?
+    pub fn floor(&self) -> Nr {
+        Nr::new(self.value.floor())
+    }
+
+    pub fn ceil(&self) -> Nr {
+        Nr::new(self.value.ceil())
+    }
+
+    /// Round to the nearest integer, with halves rounded away from zero.
+    pub fn round(&self) -> Nr {
+        Nr::new(self.value.round())
+    }
+
+    /// Round towards zero.
+    pub fn trunc(&self) -> Nr {
+        Nr::new(self.value.trunc())
+    }
+
+    /// Round to the given number of decimals, which is truncated to an integer and may be negative to round to tens etc.
+    pub fn round_to(&self, decimals: Nr) -> Nr {
+        let factor = 10f64.powf(decimals.value.trunc());
+        Nr::new((self.value * factor).round() / factor)
+    }
+
+    /// Convert to an integer for counting or indexing, failing for negative, fractional or huge numbers instead of clamping.
+    pub fn to_uint(&self) -> TildeRes<UINT> {
+        if self.value < 0.0 || self.value.fract() != 0.0 {
+            return Err(TildeError::runtime(format!("expected a non-negative whole number, but got {self}")))
+        }
+        if self.value >= UINT::MAX as f64 {
+            return Err(TildeError::runtime(format!("expected a number that fits in an integer, but got {self}")))
+        }
+        Ok(self.value as UINT)
     }
 
     pub fn abs_sqrt(&self) -> Nr {
         Nr::new(self.value.abs().sqrt())
     }
+
+    pub fn neg(&self) -> Nr {
+        Nr::new(-self.value)
+    }
+
+    pub fn abs(&self) -> Nr {
+        Nr::new(self.value.abs())
+    }
+
+    pub fn incr(&self) -> Nr {
+        Nr::new(self.value + 1.0)
+    }
+
+    pub fn decr(&self) -> Nr {
+        Nr::new(self.value - 1.0)
+    }
+
+    /// -1, 0 or 1 depending on whether the number is negative, zero or positive.
+    pub fn sign(&self) -> Nr {
+        if self.value == 0.0 {
+            return Nr::zero()
+        }
+        Nr::new(self.value.signum())
+    }
+
+    pub fn reciprocal(&self) -> Nr {
+        Nr::new(1.0 / self.value)
+    }
+
+    pub fn pow(&self, exponent: Nr) -> Nr {
+        Nr::new(self.value.powf(exponent.value))
+    }
+
+    /// Modulo where the result has the sign of the divisor, so `-1 mod 3` is 2. Zero if the divisor is zero.
+    pub fn modulo_floor(&self, divisor: Nr) -> Nr {
+        let rem = self.value % divisor.value;
+        if rem != 0.0 && (rem < 0.0) != (divisor.value < 0.0) {
+            Nr::new(rem + divisor.value)
+        } else {
+            Nr::new(rem)
+        }
+    }
+
+    /// Remainder where the result has the sign of the dividend, so `-1 rem 3` is -1. Zero if the divisor is zero.
+    pub fn modulo_trunc(&self, divisor: Nr) -> Nr {
+        Nr::new(self.value % divisor.value)
+    }
 }
 
 impl Fork for Nr {
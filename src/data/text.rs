@@ -1,6 +1,8 @@
 use ::std::fmt;
 use ::std::rc::Rc;
 
+use crate::exec::track_size;
+
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Text {
     val: Rc<String>,
@@ -8,7 +10,9 @@ pub struct Text {
 
 impl Text {
     pub fn of(val: impl Into<String>) -> Self {
-        Text { val: Rc::new(val.into()) }
+        let val = val.into();
+        track_size(val.len());
+        Text { val: Rc::new(val) }
     }
 
     pub fn empty() -> Self {
@@ -20,6 +20,39 @@ pub enum Value {
 
 pub type Values = TinyVec<[Value; 2]>;
 
+impl Value {
+    /// Name of the type, as used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Num(_) => "number",
+            Value::Txt(_) => "text",
+            Value::Arr(_) => "array",
+            Value::Func(_) => "function",
+        }
+    }
+
+    /// Whether the value counts as true for conditions. Zero, empty texts and empty arrays are false,
+    /// everything else (including all functions) is true.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Num(nr) => *nr != Nr::zero(),
+            Value::Txt(text) => text.len() > 0,
+            Value::Arr(array) => array.get(0).is_some(),
+            Value::Func(_) => true,
+        }
+    }
+
+    /// Whether all arrays in the value, including those captured by functions, have produced all their
+    /// elements, so that hashing or comparing the value does not read lazy input.
+    pub fn is_produced(&self) -> bool {
+        match self {
+            Value::Num(_) | Value::Txt(_) => true,
+            Value::Arr(array) => array.is_complete() && array.iter().all(|val| val.is_produced()),
+            Value::Func(func) => func.is_produced(),
+        }
+    }
+}
+
 impl Fork for Value {
     fn fork(&self) -> Value {
         match self {
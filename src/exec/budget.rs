@@ -0,0 +1,239 @@
+use ::std::cell::RefCell;
+use ::std::time::Duration;
+use ::std::time::Instant;
+
+use crate::ErrorKind;
+use crate::TildeError;
+use crate::TildeRes;
+
+/// Settings for a single execution of a program.
+#[derive(Debug, Clone, Default)]
+pub struct ExecConfig {
+    /// Maximum number of operations to run, including those inside closures, and elements produced by lazy arrays.
+    pub max_steps: Option<u64>,
+    /// Maximum wall-clock time that execution may take.
+    pub timeout: Option<Duration>,
+    /// Maximum total size of all arrays and texts created during execution,
+    /// counting one per array element and one per text byte. Memory is not reclaimed from this budget.
+    pub max_size: Option<u64>,
+    /// Maximum number of nested recursive calls through `fix`. If not set, a conservative default is used,
+    /// because deep recursion overflows the native stack. It is never more than [crate::MAX_DEPTH].
+    pub max_depth: Option<usize>,
+    /// Print every operation with the stack before and after it to stderr.
+    pub trace: bool,
+}
+
+#[derive(Debug)]
+struct Budget {
+    steps: u64,
+    max_steps: Option<u64>,
+    size: u64,
+    max_size: Option<u64>,
+    start: Instant,
+    deadline: Option<Instant>,
+}
+
+thread_local! {
+    static BUDGET: RefCell<Option<Budget>> = const { RefCell::new(None) };
+    static FAILED: RefCell<Option<TildeError>> = const { RefCell::new(None) };
+}
+
+/// Keeps the budget active for the current thread until dropped.
+#[must_use]
+pub struct BudgetGuard {
+    previous: Option<Budget>,
+}
+
+pub fn start_budget(config: &ExecConfig) -> BudgetGuard {
+    let start = Instant::now();
+    let budget = Budget {
+        steps: 0,
+        max_steps: config.max_steps,
+        size: 0,
+        max_size: config.max_size,
+        start,
+        deadline: config.timeout.map(|timeout| start + timeout),
+    };
+    let previous = BUDGET.with(|cell| cell.replace(Some(budget)));
+    FAILED.with(|cell| cell.take());
+    BudgetGuard { previous }
+}
+
+impl Drop for BudgetGuard {
+    fn drop(&mut self) {
+        BUDGET.with(|cell| cell.replace(self.previous.take()));
+    }
+}
+
+/// Count one executed operation, failing if this exceeds the step budget or deadline.
+/// Does nothing if there is no active budget.
+pub fn take_step() -> TildeRes<()> {
+    take_failed()?;
+    BUDGET.with(|cell| {
+        let mut budget = cell.borrow_mut();
+        let Some(budget) = budget.as_mut() else {
+            return Ok(())
+        };
+        check_limits(budget)?;
+        budget.steps += 1;
+        Ok(())
+    })
+}
+
+/// Count one element produced by a lazy array, as a step and as one unit of size. Returns false if the
+/// budget is used up, in which case no more elements should be produced, so that huge lazy arrays stop
+/// early instead of running past the limits. The error is reported like [fail_later].
+pub fn take_element() -> bool {
+    let res = BUDGET.with(|cell| {
+        let mut budget = cell.borrow_mut();
+        let Some(budget) = budget.as_mut() else {
+            return Ok(())
+        };
+        check_limits(budget)?;
+        budget.steps += 1;
+        budget.size = budget.size.saturating_add(1);
+        check_size(budget)
+    });
+    match res {
+        Ok(()) => true,
+        Err(err) => {
+            fail_later(err);
+            false
+        },
+    }
+}
+
+/// Record the size of a newly created array or text. Exceeding the budget is reported by
+/// the next call to [take_step] or [check_budget], so that creating values can stay infallible.
+pub fn track_size(size: usize) {
+    BUDGET.with(|cell| {
+        if let Some(budget) = cell.borrow_mut().as_mut() {
+            budget.size = budget.size.saturating_add(size as u64);
+        }
+    })
+}
+
+/// Record an error from code that cannot return one, like producing the elements of a lazy array.
+/// It is reported by the next call to [take_step] or [check_budget]. Only the first error is kept.
+pub fn fail_later(err: TildeError) {
+    FAILED.with(|cell| {
+        let mut failed = cell.borrow_mut();
+        if failed.is_none() {
+            *failed = Some(err);
+        }
+    })
+}
+
+/// Whether an error was recorded by [fail_later] that has not been reported yet.
+pub fn has_failed() -> bool {
+    FAILED.with(|cell| cell.borrow().is_some())
+}
+
+fn take_failed() -> TildeRes<()> {
+    match FAILED.with(|cell| cell.take()) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Fail if an error was recorded by [fail_later], or if more array elements or text bytes were created
+/// than the budget allows.
+pub fn check_budget() -> TildeRes<()> {
+    take_failed()?;
+    BUDGET.with(|cell| match cell.borrow().as_ref() {
+        Some(budget) => check_size(budget),
+        None => Ok(()),
+    })
+}
+
+fn check_limits(budget: &Budget) -> TildeRes<()> {
+    if let Some(max_steps) = budget.max_steps {
+        if budget.steps >= max_steps {
+            return Err(TildeError::new(ErrorKind::StepLimit { steps: budget.steps }))
+        }
+    }
+    check_size(budget)?;
+    if let Some(deadline) = budget.deadline {
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(TildeError::new(ErrorKind::Timeout { steps: budget.steps, elapsed: now - budget.start }))
+        }
+    }
+    Ok(())
+}
+
+fn check_size(budget: &Budget) -> TildeRes<()> {
+    match budget.max_size {
+        Some(max_size) if budget.size > max_size => Err(TildeError::new(ErrorKind::SizeLimit { steps: budget.steps, size: budget.size })),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_budget_is_unlimited() {
+        for _ in 0..1000 {
+            take_step().unwrap();
+        }
+    }
+
+    #[test]
+    fn step_limit() {
+        let _guard = start_budget(&ExecConfig { max_steps: Some(3), ..ExecConfig::default() });
+        for _ in 0..3 {
+            take_step().unwrap();
+        }
+        let err = take_step().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::StepLimit { steps: 3 });
+    }
+
+    #[test]
+    fn timeout() {
+        let _guard = start_budget(&ExecConfig { timeout: Some(Duration::ZERO), ..ExecConfig::default() });
+        let err = take_step().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Timeout { steps: 0, .. }));
+    }
+
+    #[test]
+    fn size_limit() {
+        let _guard = start_budget(&ExecConfig { max_size: Some(10), ..ExecConfig::default() });
+        track_size(6);
+        take_step().unwrap();
+        track_size(6);
+        let err = check_budget().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::SizeLimit { steps: 1, size: 12 });
+        assert!(take_step().is_err());
+    }
+
+    #[test]
+    fn elements_stop_at_limit() {
+        let _guard = start_budget(&ExecConfig { max_size: Some(2), ..ExecConfig::default() });
+        assert!(take_element());
+        assert!(take_element());
+        assert!(!has_failed());
+        assert!(!take_element());
+        assert!(has_failed());
+        let err = check_budget().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::SizeLimit { steps: 3, size: 3 });
+    }
+
+    #[test]
+    fn failed_is_reported_once() {
+        fail_later(TildeError::io("first"));
+        fail_later(TildeError::io("second"));
+        assert_eq!(check_budget().unwrap_err(), TildeError::io("first"));
+        check_budget().unwrap();
+    }
+
+    #[test]
+    fn guard_restores() {
+        {
+            let _guard = start_budget(&ExecConfig { max_steps: Some(0), ..ExecConfig::default() });
+            assert!(take_step().is_err());
+        }
+        take_step().unwrap();
+    }
+}
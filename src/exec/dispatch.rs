@@ -1,37 +1,43 @@
-use crate::exec::{BinaryExecutor, Executor, UnaryExecutor};
+use crate::exec::{BinaryExecutor, Executor, TernaryExecutor, UnaryExecutor};
 use crate::exec::stack::Stack;
 use crate::op::Op;
+use crate::TildeRes;
 use crate::Value;
 use crate::Values;
 
-pub fn dispatch_op(stack: &mut impl Stack, op: &Op) -> Values {
+pub fn dispatch_op(stack: &mut impl Stack, op: &Op) -> TildeRes<Values> {
     let ret = match op.as_executor() {
-        Executor::Nullary(exec) => exec.exec(),
+        Executor::Nullary(exec) => exec.exec()?,
         Executor::Unary(exec) => {
             let top = stack.pop();
-            dispatch_unary(exec, top)
+            dispatch_unary(exec, top)?
         },
         Executor::Binary(exec) => {
             let top = stack.pop();
             let deep = stack.pop();
-            dispatch_binary(exec, top, deep)
+            dispatch_binary(exec, top, deep)?
         }
         Executor::BinaryOpaque(exec) => {
             let top = stack.pop();
             let deep = stack.pop();
             match (deep, top) {
-                (Some(deep), Some(top)) => exec.exec_opaque(deep, top),
-                (None, Some(top)) => exec.exec_single_opaque(top),
+                (Some(deep), Some(top)) => exec.exec_opaque(deep, top)?,
+                (None, Some(top)) => exec.exec_single_opaque(top)?,
                 (Some(_), None) => unreachable!(),
-                (None, None) => exec.exec_empty(),
+                (None, None) => exec.exec_empty()?,
             }
         },
-        Executor::TernaryOpaque => todo!(),
+        Executor::Ternary(exec) => {
+            let top = stack.pop();
+            let middle = stack.pop();
+            let deep = stack.pop();
+            dispatch_ternary(exec, top, middle, deep)?
+        },
     };
-    ret
+    Ok(ret)
 }
 
-pub fn dispatch_unary(exec: &dyn UnaryExecutor, top: Option<Value>) -> Values {
+pub fn dispatch_unary(exec: &dyn UnaryExecutor, top: Option<Value>) -> TildeRes<Values> {
     match top {
         Some(Value::Num(top)) => exec.exec_n(top),
         Some(Value::Txt(top)) => exec.exec_t(top),
@@ -41,7 +47,7 @@ pub fn dispatch_unary(exec: &dyn UnaryExecutor, top: Option<Value>) -> Values {
     }
 }
 
-pub fn dispatch_binary(exec: &dyn BinaryExecutor, top: Option<Value>, deep: Option<Value>) -> Values {
+pub fn dispatch_binary(exec: &dyn BinaryExecutor, top: Option<Value>, deep: Option<Value>) -> TildeRes<Values> {
     match (deep, top) {
         (Some(Value::Num(deep)), Some(Value::Num(top))) => exec.exec_nn(deep, top),
         (Some(Value::Num(deep)), Some(Value::Txt(top))) => exec.exec_nt(deep, top),
@@ -67,3 +73,120 @@ pub fn dispatch_binary(exec: &dyn BinaryExecutor, top: Option<Value>, deep: Opti
         (Some(top), None) => unreachable!(),
     }
 }
+
+/// Values that are missing from the stack may only be missing from the deep end, except in closures,
+/// where the free value may be missing. In that case, the remaining values move up.
+pub fn dispatch_ternary(exec: &dyn TernaryExecutor, top: Option<Value>, middle: Option<Value>, deep: Option<Value>) -> TildeRes<Values> {
+    match (deep, middle, top) {
+        (Some(deep), Some(middle), Some(Value::Func(top))) => exec.exec_top_f(deep, middle, top),
+        (Some(deep), Some(Value::Func(middle)), Some(top)) => exec.exec_middle_f(deep, middle, top),
+        (Some(Value::Func(deep)), Some(middle), Some(top)) => exec.exec_deep_f(deep, middle, top),
+        (Some(deep), Some(middle), Some(top)) => exec.exec_values(deep, middle, top),
+        (Some(deep), Some(top), None) |
+        (Some(deep), None, Some(top)) |
+        (None, Some(deep), Some(top)) => exec.exec_double(deep, top),
+        (Some(single), None, None) |
+        (None, Some(single), None) |
+        (None, None, Some(single)) => exec.exec_single(single),
+        (None, None, None) => exec.exec_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::std::any::Any;
+    use ::std::borrow::Cow;
+
+    use crate::Array;
+    use crate::compile::GolfWord;
+    use crate::Func;
+    use crate::op::OpTyp;
+    use crate::op::testing::nr;
+    use crate::op::Plus;
+    use crate::values;
+
+    use super::*;
+
+    /// Collects its inputs into an array, to show which values ended up where.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Collect3;
+
+    impl OpTyp for Collect3 {
+        fn description(&self) -> &'static str {
+            "collect three values"
+        }
+
+        fn long_code(&self) -> Cow<'static, str> {
+            Cow::Borrowed("collect3")
+        }
+
+        fn golf_code(&self) -> Option<GolfWord> {
+            None
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_executor(&self) -> Executor {
+            Executor::Ternary(self)
+        }
+    }
+
+    impl TernaryExecutor for Collect3 {
+        fn exec_values(&self, deep: Value, middle: Value, top: Value) -> TildeRes<Values> {
+            Ok(values![Array::of(vec![deep, middle, top])])
+        }
+
+        fn exec_double(&self, deep: Value, top: Value) -> TildeRes<Values> {
+            Ok(values![Array::of(vec![deep, top])])
+        }
+
+        fn exec_single(&self, single: Value) -> TildeRes<Values> {
+            Ok(values![Array::of(vec![single])])
+        }
+
+        fn exec_empty(&self) -> TildeRes<Values> {
+            Ok(values![Array::of(Vec::<Value>::new())])
+        }
+    }
+
+    fn collect3() -> Op {
+        Op::of(Collect3)
+    }
+
+    #[test]
+    fn ternary_from_stack() {
+        let mut stack = vec![nr(1.0), nr(2.0), nr(3.0), nr(4.0)];
+        let res = dispatch_op(&mut stack, &collect3()).unwrap();
+        assert_eq!(res, values![Array::of(vec![nr(2.0), nr(3.0), nr(4.0)])]);
+        assert_eq!(stack, vec![nr(1.0)]);
+    }
+
+    #[test]
+    fn ternary_too_few_values() {
+        let mut stack = vec![nr(1.0), nr(2.0)];
+        let res = dispatch_op(&mut stack, &collect3()).unwrap();
+        assert_eq!(res, values![Array::of(vec![nr(1.0), nr(2.0)])]);
+        let res = dispatch_op(&mut vec![], &collect3()).unwrap();
+        assert_eq!(res, values![Array::of(Vec::<Value>::new())]);
+    }
+
+    #[test]
+    fn ternary_captures_each_position() {
+        let func = || Value::Func(Func::new().with_bin_deep(Plus::new(), nr(5.0)));
+        for (stack, expected) in [
+            (vec![nr(1.0), nr(2.0), func()], vec![nr(1.0), nr(2.0), nr(9.0)]),
+            (vec![nr(1.0), func(), nr(3.0)], vec![nr(1.0), nr(9.0), nr(3.0)]),
+            (vec![func(), nr(2.0), nr(3.0)], vec![nr(9.0), nr(2.0), nr(3.0)]),
+        ] {
+            let mut stack = stack;
+            let mut res = dispatch_op(&mut stack, &collect3()).unwrap();
+            let Some(Value::Func(closure)) = res.pop() else {
+                panic!("expected a closure")
+            };
+            let applied = closure.run_on_single(nr(4.0)).unwrap();
+            assert_eq!(applied, values![Array::of(expected)]);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use ::std::cell::RefCell;
+use ::std::io::Write;
+use ::std::rc::Rc;
+
+use crate::ErrorKind;
+use crate::output::write_printed;
+use crate::OutputMode;
+use crate::TildeError;
+use crate::TildeRes;
+use crate::Value;
+
+struct Printer {
+    out: Rc<RefCell<dyn Write>>,
+    mode: OutputMode,
+}
+
+thread_local! {
+    static PRINTER: RefCell<Option<Printer>> = const { RefCell::new(None) };
+    static RETURNED: RefCell<Option<Value>> = const { RefCell::new(None) };
+}
+
+/// Keeps the output writer available to running programs for the current thread until dropped.
+#[must_use]
+pub struct PrintGuard {
+    previous: Option<Printer>,
+}
+
+/// Let programs write values to `out` while running, formatted for the given output mode.
+pub fn start_printing(out: Rc<RefCell<dyn Write>>, mode: OutputMode) -> PrintGuard {
+    let previous = PRINTER.with(|cell| cell.replace(Some(Printer { out, mode })));
+    PrintGuard { previous }
+}
+
+impl Drop for PrintGuard {
+    fn drop(&mut self) {
+        PRINTER.with(|cell| cell.replace(self.previous.take()));
+    }
+}
+
+/// Write the value to the output and flush it, so it appears before the program ends.
+/// Does nothing if there is no active output writer.
+pub fn print_value(value: &Value) -> TildeRes<()> {
+    PRINTER.with(|cell| {
+        let printer = cell.borrow();
+        let Some(printer) = printer.as_ref() else {
+            return Ok(())
+        };
+        let mut out = printer.out.borrow_mut();
+        write_printed(&mut &mut *out, value, printer.mode)
+            .and_then(|_| out.flush())
+            .map_err(|err| TildeError::io(format!("failed to print value, err: {err}")))
+    })
+}
+
+/// Stop the program, with the value (if any) as the only value on the final stack. This unwinds through
+/// running closures as an error, which the executor turns back into a result using [take_returned].
+pub fn return_value(value: Option<Value>) -> TildeError {
+    RETURNED.with(|cell| cell.replace(value));
+    TildeError::new(ErrorKind::Return)
+}
+
+/// If the error came from [return_value], give the returned value, otherwise give back the error.
+pub fn take_returned(err: TildeError) -> TildeRes<Option<Value>> {
+    if err.kind() == &ErrorKind::Return {
+        Ok(RETURNED.with(|cell| cell.take()))
+    } else {
+        Err(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Nr;
+
+    use super::*;
+
+    #[test]
+    fn print_to_writer() {
+        let out = Rc::new(RefCell::new(Vec::<u8>::new()));
+        {
+            let _printer = start_printing(out.clone(), OutputMode::Lines);
+            print_value(&Value::Num(Nr::new(1.0))).unwrap();
+            print_value(&Value::Num(Nr::new(2.0))).unwrap();
+        }
+        print_value(&Value::Num(Nr::new(3.0))).unwrap();
+        assert_eq!(String::from_utf8(out.take()).unwrap(), "1\n2\n");
+    }
+
+    #[test]
+    fn returned_value_is_taken_once() {
+        let err = return_value(Some(Value::Num(Nr::new(1.0))));
+        assert_eq!(take_returned(err).unwrap(), Some(Value::Num(Nr::new(1.0))));
+        assert_eq!(take_returned(return_value(None)).unwrap(), None);
+        assert!(take_returned(TildeError::runtime("other")).is_err());
+    }
+}
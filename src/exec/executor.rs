@@ -7,6 +7,8 @@ use crate::Func;
 use crate::Nr;
 use crate::op::OpTyp;
 use crate::Text;
+use crate::TildeError;
+use crate::TildeRes;
 use crate::Value;
 use crate::values;
 use crate::Values;
@@ -23,118 +25,184 @@ pub enum Executor<'a> {
     Binary(&'a dyn BinaryExecutor),
     ///
     BinaryOpaque(&'a dyn BinaryOpaqueExecutor),
-    ///
-    TernaryOpaque,
+    /// Consumes three stack values
+    Ternary(&'a dyn TernaryExecutor),
+}
+
+impl Executor<'_> {
+    /// How many stack values the operation consumes, if they are available.
+    pub fn input_count(&self) -> usize {
+        match self {
+            Executor::Nullary(_) => 0,
+            Executor::Unary(_) => 1,
+            Executor::Binary(_) => 2,
+            Executor::BinaryOpaque(_) => 2,
+            Executor::Ternary(_) => 3,
+        }
+    }
+}
+
+/// Error for a combination of input types that an operation does not support.
+pub fn unsupported<T>(op: &(impl OpTyp + ?Sized), types: &[&'static str]) -> TildeRes<T> {
+    Err(TildeError::typ(op.long_code(), types))
 }
 
 pub trait NullaryExecutor: OpTyp {
-    fn exec(&self) -> Values;
+    fn exec(&self) -> TildeRes<Values>;
 }
 
 pub trait UnaryExecutor: OpTyp {
 
-    fn exec_n(&self, value: Nr) -> Values;
+    fn exec_n(&self, value: Nr) -> TildeRes<Values>;
 
-    fn exec_t(&self, value: Text) -> Values;
+    fn exec_t(&self, value: Text) -> TildeRes<Values>;
 
-    fn exec_a(&self, value: Array) -> Values;
+    fn exec_a(&self, value: Array) -> TildeRes<Values>;
 
-    fn exec_f(&self, value: Func) -> Values {
-        values![Value::Func(value.with_unary(self.clone_op()))]
+    fn exec_f(&self, value: Func) -> TildeRes<Values> {
+        Ok(values![Value::Func(value.with_unary(self.clone_op()))])
     }
 
     /// Fallback for if the stack is empty
-    fn exec_empty(&self) -> Values;
+    fn exec_empty(&self) -> TildeRes<Values>;
 }
 
 pub trait BinaryExecutor: OpTyp {
 
-    fn exec_nn(&self, deep: Nr, top: Nr) -> Values;
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values>;
 
-    fn exec_nt(&self, deep: Nr, top: Text) -> Values;
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values>;
 
-    fn exec_na(&self, deep: Nr, top: Array) -> Values;
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values>;
 
     //TODO @mark: can current_op be removed? same as self?
-    fn exec_nf(&self, deep: Nr, top: Func) -> Values {
-        values![Value::Func(top.with_bin_top(self.clone_op(), Value::Num(deep)))]
+    fn exec_nf(&self, deep: Nr, top: Func) -> TildeRes<Values> {
+        Ok(top.with_bin_top(self.clone_op(), Value::Num(deep)))
     }
 
-    fn exec_tn(&self, deep: Text, top: Nr) -> Values;
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values>;
 
-    fn exec_tt(&self, deep: Text, top: Text) -> Values;
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values>;
 
-    fn exec_ta(&self, deep: Text, top: Array) -> Values;
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values>;
 
-    fn exec_tf(&self, deep: Text, top: Func) -> Values {
-        values![Value::Func(top.with_bin_top(self.clone_op(), Value::Txt(deep)))]
+    fn exec_tf(&self, deep: Text, top: Func) -> TildeRes<Values> {
+        Ok(top.with_bin_top(self.clone_op(), Value::Txt(deep)))
     }
 
-    fn exec_an(&self, deep: Array, top: Nr) -> Values;
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values>;
 
-    fn exec_at(&self, deep: Array, top: Text) -> Values;
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values>;
 
-    fn exec_aa(&self, deep: Array, top: Array) -> Values;
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values>;
 
-    fn exec_af(&self, deep: Array, top: Func) -> Values {
-        values![Value::Func(top.with_bin_top(self.clone_op(), Value::Arr(deep)))]
+    fn exec_af(&self, deep: Array, top: Func) -> TildeRes<Values> {
+        Ok(top.with_bin_top(self.clone_op(), Value::Arr(deep)))
     }
 
-    fn exec_fn(&self, deep: Func, top: Nr) -> Values {
-        values![Value::Func(deep.with_bin_deep(self.clone_op(), Value::Num(top)))]
+    fn exec_fn(&self, deep: Func, top: Nr) -> TildeRes<Values> {
+        Ok(values![Value::Func(deep.with_bin_deep(self.clone_op(), Value::Num(top)))])
     }
 
-    fn exec_ft(&self, deep: Func, top: Text) -> Values {
-        values![Value::Func(deep.with_bin_deep(self.clone_op(), Value::Txt(top)))]
+    fn exec_ft(&self, deep: Func, top: Text) -> TildeRes<Values> {
+        Ok(values![Value::Func(deep.with_bin_deep(self.clone_op(), Value::Txt(top)))])
     }
 
-    fn exec_fa(&self, deep: Func, top: Array) -> Values {
-        values![Value::Func(deep.with_bin_deep(self.clone_op(), Value::Arr(top)))]
+    fn exec_fa(&self, deep: Func, top: Array) -> TildeRes<Values> {
+        Ok(values![Value::Func(deep.with_bin_deep(self.clone_op(), Value::Arr(top)))])
     }
 
-    fn exec_ff(&self, deep: Func, top: Func) -> Values {
-        values![Value::Func(top.with_bin_top(self.clone_op(), Value::Func(deep)))]
+    fn exec_ff(&self, deep: Func, top: Func) -> TildeRes<Values> {
+        Ok(top.with_bin_top(self.clone_op(), Value::Func(deep)))
     }
 
     /// Fallback for if there is only 1 value on the stack and it is a number
-    fn exec_single_n(&self, single: Nr) -> Values;
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values>;
 
     /// Fallback for if there is only 1 value on the stack and it is a text
-    fn exec_single_t(&self, single: Text) -> Values;
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values>;
 
     /// Fallback for if there is only 1 value on the stack and it is a array
-    fn exec_single_a(&self, single: Array) -> Values;
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values>;
 
     /// Fallback for if there is only 1 value on the stack and it is a function
-    fn exec_single_f(&self, single: Func) -> Values {
-        todo!()
+    fn exec_single_f(&self, single: Func) -> TildeRes<Values> {
+        unsupported(self, &["function"])
     }
 
     /// Fallback for if the stack is empty
-    fn exec_empty(&self) -> Values;
+    fn exec_empty(&self) -> TildeRes<Values>;
 
-    fn exec_elemwise(&self, deep: Array, top: Array) -> Values where Self: Sized {
+    fn exec_elemwise(&self, deep: Array, top: Array) -> TildeRes<Values> where Self: Sized {
         let mut new = Vec::with_capacity(deep.len());
         for (ix, deep_elem) in deep.iter().enumerate() {
-            let top_elem = top.index(Nr::from(ix));
-            let mut new_val = dispatch_binary(self, Some(deep_elem.fork()), Some(top_elem));
-            if new_val.len() == 1 {
-                new.push(new_val.into_iter().next().unwrap())
+            let top_elem = top.get(ix).unwrap_or_default();
+            new.push(single_or_array(dispatch_binary(self, Some(top_elem), Some(deep_elem.fork()))?))
+        }
+        Ok(values![Value::Arr(Array::of(new))])
+    }
+
+    /// Apply to every element of the array together with the other value, which stays on its own side.
+    /// Like [BinaryExecutor::exec_elemwise], an element with several results becomes an array of them.
+    fn exec_each(&self, arr: Array, other: Value, arr_is_deep: bool) -> TildeRes<Values> where Self: Sized {
+        let mut new = Vec::new();
+        for elem in arr.iter() {
+            new.push(single_or_array(if arr_is_deep {
+                dispatch_binary(self, Some(other.fork()), Some(elem))?
             } else {
-                new.push(Value::Arr(Array::of(new_val.drain(..).collect())))
-            }
+                dispatch_binary(self, Some(elem), Some(other.fork()))?
+            }))
         }
-        values![Value::Arr(Array::of(new))]
+        Ok(values![Value::Arr(Array::of(new))])
+    }
+}
+
+/// The value if there is exactly one, or otherwise an array of all of them.
+fn single_or_array(mut values: Values) -> Value {
+    if values.len() == 1 {
+        values.pop().unwrap()
+    } else {
+        Value::Arr(Array::of(values.drain(..).collect()))
     }
 }
 
 pub trait BinaryOpaqueExecutor: OpTyp {
 
-    fn exec_opaque(&self, deep: Value, top: Value) -> Values;
+    fn exec_opaque(&self, deep: Value, top: Value) -> TildeRes<Values>;
+
+    /// Fallback for if there is only 1 value on the stack
+    fn exec_single_opaque(&self, single: Value) -> TildeRes<Values>;
+
+    /// Fallback for if the stack is empty
+    fn exec_empty(&self) -> TildeRes<Values>;
+}
+
+pub trait TernaryExecutor: OpTyp {
+
+    /// All three values are available, and none of them is a function
+    fn exec_values(&self, deep: Value, middle: Value, top: Value) -> TildeRes<Values>;
+
+    /// The top value is a function, which will provide the top value when it is applied
+    fn exec_top_f(&self, deep: Value, middle: Value, top: Func) -> TildeRes<Values> {
+        Ok(top.with_tern_top(self.clone_op(), deep, middle))
+    }
+
+    /// The middle value is a function (but the top is not), which will provide the middle value when it is applied
+    fn exec_middle_f(&self, deep: Value, middle: Func, top: Value) -> TildeRes<Values> {
+        Ok(middle.with_tern_middle(self.clone_op(), deep, top))
+    }
+
+    /// The deep value is a function (but the others are not), which will provide the deep value when it is applied
+    fn exec_deep_f(&self, deep: Func, middle: Value, top: Value) -> TildeRes<Values> {
+        Ok(values![Value::Func(deep.with_tern_deep(self.clone_op(), middle, top))])
+    }
+
+    /// Fallback for if there are only 2 values on the stack
+    fn exec_double(&self, deep: Value, top: Value) -> TildeRes<Values>;
 
     /// Fallback for if there is only 1 value on the stack
-    fn exec_single_opaque(&self, single: Value) -> Values;
+    fn exec_single(&self, single: Value) -> TildeRes<Values>;
 
     /// Fallback for if the stack is empty
-    fn exec_empty(&self) -> Values;
+    fn exec_empty(&self) -> TildeRes<Values>;
 }
@@ -1,46 +1,66 @@
 use crate::compile::Prog;
+use crate::data::Fork;
+use crate::exec::effects::take_returned;
+use crate::exec::recursion::start_recursion;
+use crate::exec::trace::start_trace;
+use crate::exec::vars::start_variables;
+use crate::exec::vars::with_variables;
 use crate::op::Apply;
 use crate::op::Op;
 use crate::tilde_log;
 use crate::TildeRes;
 use crate::Value;
 
+pub use self::budget::check_budget;
+pub use self::budget::ExecConfig;
+pub use self::budget::start_budget;
+pub use self::budget::fail_later;
+pub use self::budget::has_failed;
+pub use self::budget::take_element;
+pub use self::budget::take_step;
+pub use self::budget::track_size;
 pub use self::dispatch::dispatch_binary;
 pub use self::dispatch::dispatch_op;
 pub use self::dispatch::dispatch_unary;
+pub use self::effects::print_value;
+pub use self::effects::return_value;
+pub use self::effects::start_printing;
 pub use self::executor::BinaryExecutor;
 pub use self::executor::BinaryOpaqueExecutor;
 pub use self::executor::Executor;
 pub use self::executor::NullaryExecutor;
+pub use self::executor::TernaryExecutor;
 pub use self::executor::UnaryExecutor;
+pub use self::executor::unsupported;
+pub use self::recursion::MAX_DEPTH;
+pub use self::recursion::recurse;
+pub use self::recursion::RUN_STACK_SIZE;
+pub use self::recursion::run_fixed;
 pub use self::stack::new_large_stack;
 pub use self::stack::new_small_stack;
 pub use self::stack::Stack;
+pub use self::trace::enter_closure;
+pub use self::trace::trace_before;
+pub use self::trace::trace_op;
+pub use self::vars::load_var;
+pub use self::vars::store_var;
+pub use self::vars::Variables;
 
+mod budget;
+mod effects;
 mod executor;
 mod dispatch;
+mod recursion;
 mod stack;
+mod trace;
+mod vars;
 
 pub fn execute(
     prog: Prog,
     inp: Value,
+    config: &ExecConfig,
 ) -> TildeRes<Value> {
-    let mut i = 0;
-    let mut stack = new_large_stack();
-    stack.push(inp);
-    while let Some(op) = prog.get(i) {
-        tilde_log!("stack before {:?}: {}", op, stack.as_debug_str());
-        let ret = dispatch_op(&mut stack, op);
-        stack.push_all(ret);
-        i += 1;
-    }
-    if let Some(Value::Func(func)) = stack.peek() {
-        tilde_log!("all ops done, but top of stack is function, adding apply op");
-        dbg!(&stack);  //TODO @mark: TEMPORARY! REMOVE THIS!
-        let ret = dispatch_op(&mut stack, &Op::of(Apply));
-        stack.push_all(ret);
-    }
-    tilde_log!("final stack: {}", stack.as_debug_str());
+    let mut stack = execute_stack(prog, vec![inp], config)?;
     Ok(match stack.pop() {
         Some(top) => {
             tilde_log!("execution done, top item out of {} is {:?}", stack.size(), &top);
@@ -52,3 +72,177 @@ pub fn execute(
         }
     })
 }
+
+/// Like [execute], but start with any number of inputs on the stack, and return the whole
+/// final stack. Both have the top value last.
+pub fn execute_stack(
+    prog: Prog,
+    inputs: Vec<Value>,
+    config: &ExecConfig,
+) -> TildeRes<Vec<Value>> {
+    let _budget = start_budget(config);
+    let _trace = start_trace(config.trace);
+    let _recursion = start_recursion(config.max_depth);
+    let input = inputs.last().map(|inp| inp.fork());
+    let _vars = start_variables(input.as_ref().map(|inp| inp.fork()).unwrap_or_default());
+    let mut stack = new_large_stack();
+    stack.extend(inputs);
+    let res = run_ops(&prog, &mut stack)
+        .and_then(|_| apply_remaining_funcs(&mut stack, input, prog.len()));
+    if let Err(err) = res {
+        let returned = take_returned(err)?;
+        stack.clear();
+        stack.extend(returned);
+    }
+    tilde_log!("final stack: {}", stack.as_debug_str());
+    Ok(stack)
+}
+
+/// When the program ends with a function on top of the stack, apply it, and keep applying while the
+/// result is a function. Each function is applied to the value below it, or if it is the only value
+/// on the stack, to the original input. The original input is used at most once, after that a lone
+/// function is applied without argument.
+fn apply_remaining_funcs(stack: &mut Vec<Value>, mut input: Option<Value>, mut i: usize) -> TildeRes<()> {
+    let apply = Op::of(Apply);
+    while let Some(Value::Func(_)) = stack.last() {
+        tilde_log!("all ops done, but top of stack is function, adding apply op");
+        if stack.len() == 1 {
+            if let Some(input) = input.take() {
+                stack.insert(0, input);
+            }
+        }
+        let before = trace_before(stack);
+        let ret = take_step()
+            .and_then(|_| dispatch_op(stack, &apply))
+            .and_then(|ret| check_budget().map(|_| ret))
+            .map_err(|err| err.at_op(i, None))?;
+        stack.push_all(ret);
+        trace_op(i, &apply, before, stack);
+        i += 1;
+    }
+    Ok(())
+}
+
+/// Run the program on an existing stack and variables, leaving all results on it.
+/// Unlike [execute], this does not apply a function left on top of the stack.
+/// If the program returns early, the stack is replaced by the returned value.
+pub fn execute_on_stack(
+    prog: &Prog,
+    stack: &mut impl Stack,
+    variables: &mut Variables,
+    config: &ExecConfig,
+) -> TildeRes<()> {
+    let _budget = start_budget(config);
+    let _trace = start_trace(config.trace);
+    let _recursion = start_recursion(config.max_depth);
+    if let Err(err) = with_variables(variables, || run_ops(prog, stack)) {
+        let returned = take_returned(err)?;
+        while stack.pop().is_some() {}
+        if let Some(value) = returned {
+            stack.push(value);
+        }
+    }
+    Ok(())
+}
+
+fn run_ops(prog: &Prog, stack: &mut impl Stack) -> TildeRes<()> {
+    let mut i = 0;
+    while let Some(op) = prog.get(i) {
+        tilde_log!("stack before {:?}: {}", op, stack.as_debug_str());
+        let before = trace_before(stack);
+        let ret = take_step()
+            .and_then(|_| dispatch_op(stack, op))
+            .and_then(|ret| check_budget().map(|_| ret))
+            .map_err(|err| err.at_op(i, prog.span(i)))?;
+        stack.push_all(ret);
+        trace_op(i, op, before, stack);
+        i += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile::parse;
+    use crate::ErrorKind;
+    use crate::Nr;
+    use crate::RunMode;
+    use crate::Span;
+    use crate::Text;
+
+    use super::*;
+
+    #[test]
+    fn unset_variable_is_input() {
+        let prog = parse("1 =x drop b x", RunMode::Any).unwrap();
+        let inp = Value::Txt(Text::of("input"));
+        let stack = execute_stack(prog, vec![inp.fork()], &ExecConfig::default()).unwrap();
+        assert_eq!(stack, vec![inp.fork(), inp, Value::Num(Nr::new(1.0))]);
+    }
+
+    #[test]
+    fn unsupported_types_give_error() {
+        let prog = parse("'a' 'b' minus", RunMode::Any).unwrap();
+        let err = execute(prog, Value::Txt(Text::empty()), &ExecConfig::default()).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::Type { op: "minus".to_owned(), types: vec!["text", "text"] });
+        assert_eq!(err.op_index(), Some(2));
+        assert_eq!(err.span(), Some(Span::new(8, 13)));
+    }
+
+    #[test]
+    fn step_limit_reports_progress() {
+        let prog = parse("1 2 plus 3 plus", RunMode::Any).unwrap();
+        let config = ExecConfig { max_steps: Some(3), ..ExecConfig::default() };
+        let err = execute(prog, Value::Txt(Text::empty()), &config).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::StepLimit { steps: 3 });
+        assert_eq!(err.op_index(), Some(3));
+    }
+
+    #[test]
+    fn trace_keeps_result() {
+        let prog = parse("3 arg 1 plus 2 mul apply", RunMode::Any).unwrap();
+        let config = ExecConfig { trace: true, ..ExecConfig::default() };
+        let res = execute(prog, Value::Txt(Text::empty()), &config).unwrap();
+        assert_eq!(res, Value::Num(Nr::new(8.0)));
+    }
+
+    #[test]
+    fn size_limit_on_growth() {
+        let prog = parse("'hello' 'world' concat dup concat", RunMode::Any).unwrap();
+        let config = ExecConfig { max_size: Some(15), ..ExecConfig::default() };
+        let err = execute(prog, Value::Txt(Text::empty()), &config).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::SizeLimit { size: 30, .. }), "{err:?}");
+        assert_eq!(err.op_index(), Some(4));
+    }
+
+    #[test]
+    fn implicit_apply_to_value_below() {
+        let prog = parse("2 arg 1 plus", RunMode::Any).unwrap();
+        let res = execute_stack(prog, vec![Value::Num(Nr::new(5.0))], &ExecConfig::default()).unwrap();
+        assert_eq!(res, vec![Value::Num(Nr::new(5.0)), Value::Num(Nr::new(3.0))]);
+    }
+
+    #[test]
+    fn implicit_apply_to_original_input() {
+        let prog = parse("drop arg 1 plus", RunMode::Any).unwrap();
+        let res = execute(prog, Value::Num(Nr::new(5.0)), &ExecConfig::default()).unwrap();
+        assert_eq!(res, Value::Num(Nr::new(6.0)));
+    }
+
+    #[test]
+    fn implicit_apply_without_argument() {
+        let prog = parse("arg 1 plus", RunMode::Any).unwrap();
+        let err = execute_stack(prog, vec![], &ExecConfig::default()).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::Type { op: "plus".to_owned(), types: vec!["number"] });
+        assert_eq!(err.op_index(), Some(3));
+    }
+
+    #[test]
+    fn step_limit_counts_closure_steps() {
+        let prog = parse("3 arg 1 plus 2 mul apply", RunMode::Any).unwrap();
+        let config = ExecConfig { max_steps: Some(8), ..ExecConfig::default() };
+        let err = execute(prog, Value::Txt(Text::empty()), &config).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::StepLimit { steps: 8 });
+        assert_eq!(err.op_index(), Some(6));
+    }
+}
@@ -0,0 +1,133 @@
+use ::std::cell::RefCell;
+
+use ::fnv::FnvHashMap;
+
+use crate::data::Fork;
+use crate::ErrorKind;
+use crate::Func;
+use crate::TildeError;
+use crate::TildeRes;
+use crate::Value;
+use crate::Values;
+
+/// Recursion depth limit if none is configured. Every level uses native stack, so this is kept low enough
+/// to not overflow small (e.g. 2MB) thread stacks in debug builds.
+pub const DEFAULT_MAX_DEPTH: usize = 50;
+
+/// Highest recursion depth limit that can be configured. Deeper limits are lowered to this. It only fits
+/// on a thread with [RUN_STACK_SIZE] of native stack, which is what the command line runs programs on.
+pub const MAX_DEPTH: usize = 2_000;
+
+/// Native stack size of the thread that runs programs from the command line, so that [MAX_DEPTH] fits.
+pub const RUN_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+#[derive(Debug)]
+struct Recursion {
+    /// Functions that are running through `fix`, innermost last, and whether each memoizes.
+    targets: Vec<(Func, bool)>,
+    max_depth: usize,
+    /// Results of memoizing functions, by function and argument.
+    memo: FnvHashMap<(Func, Value), Values>,
+}
+
+impl Recursion {
+    fn new(max_depth: usize) -> Self {
+        Recursion {
+            targets: Vec::new(),
+            max_depth,
+            memo: FnvHashMap::default(),
+        }
+    }
+}
+
+thread_local! {
+    static RECURSION: RefCell<Recursion> = RefCell::new(Recursion::new(DEFAULT_MAX_DEPTH));
+}
+
+/// Keeps a fresh recursion state with the given depth limit for the current thread until dropped.
+#[must_use]
+pub struct RecursionGuard {
+    previous: Option<Recursion>,
+}
+
+pub fn start_recursion(max_depth: Option<usize>) -> RecursionGuard {
+    let recursion = Recursion::new(max_depth.unwrap_or(DEFAULT_MAX_DEPTH).min(MAX_DEPTH));
+    let previous = RECURSION.with(|cell| cell.replace(recursion));
+    RecursionGuard { previous: Some(previous) }
+}
+
+impl Drop for RecursionGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            RECURSION.with(|cell| cell.replace(previous));
+        }
+    }
+}
+
+/// Run the function on the argument, with the function as the target of [recurse] while it runs.
+/// Fails if this is nested deeper than the depth limit. If `memo` is set, results are reused
+/// for arguments that were seen before during this execution. Arguments with lazy arrays that are
+/// not fully produced are not memoized, because finding them would read all their elements.
+pub fn run_fixed(func: &Func, memo: bool, arg: Value) -> TildeRes<Values> {
+    let key = if memo && arg.is_produced() && func.is_produced() {
+        let key = (func.fork(), arg.fork());
+        if let Some(known) = RECURSION.with(|cell| cell.borrow().memo.get(&key).map(fork_values)) {
+            return Ok(known)
+        }
+        Some(key)
+    } else {
+        None
+    };
+    RECURSION.with(|cell| {
+        let mut recursion = cell.borrow_mut();
+        if recursion.targets.len() >= recursion.max_depth {
+            return Err(TildeError::new(ErrorKind::DepthLimit { depth: recursion.max_depth }))
+        }
+        recursion.targets.push((func.fork(), memo));
+        Ok(())
+    })?;
+    let res = func.run_on_single(arg);
+    RECURSION.with(|cell| cell.borrow_mut().targets.pop());
+    let res = res?;
+    if let Some(key) = key {
+        RECURSION.with(|cell| cell.borrow_mut().memo.insert(key, fork_values(&res)));
+    }
+    Ok(res)
+}
+
+/// Run the innermost function that is running through `fix` again, on a new argument.
+pub fn recurse(arg: Value) -> TildeRes<Values> {
+    let Some((func, memo)) = RECURSION.with(|cell| cell.borrow().targets.last()
+            .map(|(func, memo)| (func.fork(), *memo))) else {
+        return Err(TildeError::runtime("'recurse' can only be used inside a function that is run by 'fix'"))
+    };
+    run_fixed(&func, memo, arg)
+}
+
+fn fork_values(values: &Values) -> Values {
+    values.iter().map(|value| value.fork()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Nr;
+
+    use super::*;
+
+    #[test]
+    fn recurse_outside_fix() {
+        let _recursion = start_recursion(None);
+        let err = recurse(Value::Num(Nr::new(1.0))).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Runtime(_)), "{err:?}");
+    }
+
+    #[test]
+    fn nested_start_restores_depth() {
+        let _outer = start_recursion(Some(3));
+        {
+            let _inner = start_recursion(Some(5));
+            assert_eq!(RECURSION.with(|cell| cell.borrow().max_depth), 5);
+        }
+        assert_eq!(RECURSION.with(|cell| cell.borrow().max_depth), 3);
+    }
+}
@@ -8,7 +8,7 @@ pub fn new_small_stack() -> Values {
     Values::new()
 }
 
-pub fn new_large_stack() -> impl Stack {
+pub fn new_large_stack() -> Vec<Value> {
     Vec::new()
 }
 
@@ -0,0 +1,71 @@
+use ::std::cell::Cell;
+
+use crate::exec::Stack;
+use crate::op::Op;
+
+thread_local! {
+    static TRACE_ON: Cell<bool> = const { Cell::new(false) };
+    static CLOSURE_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Keeps tracing enabled or disabled for the current thread until dropped.
+#[must_use]
+pub struct TraceGuard {
+    previous: bool,
+}
+
+pub fn start_trace(enabled: bool) -> TraceGuard {
+    let previous = TRACE_ON.with(|on| on.replace(enabled));
+    TraceGuard { previous }
+}
+
+impl Drop for TraceGuard {
+    fn drop(&mut self) {
+        TRACE_ON.with(|on| on.set(self.previous));
+    }
+}
+
+/// Marks that operations are run inside a closure, so that they are indented in the trace.
+#[must_use]
+pub struct ClosureTraceGuard;
+
+pub fn enter_closure() -> ClosureTraceGuard {
+    CLOSURE_DEPTH.with(|depth| depth.set(depth.get() + 1));
+    ClosureTraceGuard
+}
+
+impl Drop for ClosureTraceGuard {
+    fn drop(&mut self) {
+        CLOSURE_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Stack description before an operation, only if tracing is enabled, to be passed to [trace_op].
+pub fn trace_before(stack: &impl Stack) -> Option<String> {
+    if TRACE_ON.with(|on| on.get()) {
+        Some(stack_str(stack))
+    } else {
+        None
+    }
+}
+
+/// Print an executed operation and the stack before and after it to stderr. Unlike `tilde_log!`, this
+/// also works in release mode, but only if it was enabled for this execution.
+pub fn trace_op(index: usize, op: &Op, before: Option<String>, after: &impl Stack) {
+    let Some(before) = before else {
+        return
+    };
+    let indent = "  ".repeat(CLOSURE_DEPTH.with(|depth| depth.get()));
+    let golf = match op.golf_code() {
+        Some(golf) => format!(" ({golf})"),
+        None => String::new(),
+    };
+    eprintln!("{indent}#{index} {}{golf}: {before} -> {}", op.long_code(), stack_str(after));
+}
+
+fn stack_str(stack: &impl Stack) -> String {
+    if stack.size() == 0 {
+        return "(empty)".to_owned()
+    }
+    stack.as_debug_str()
+}
@@ -0,0 +1,125 @@
+use ::std::cell::RefCell;
+use ::std::mem;
+
+use ::fnv::FnvHashMap;
+
+use crate::data::Fork;
+use crate::Value;
+
+/// Variable values, which can be kept between runs with [with_variables].
+#[derive(Debug, Default)]
+pub struct Variables {
+    values: FnvHashMap<String, Value>,
+    /// Value of variables that have not been stored yet, which is the program input.
+    unset: Value,
+}
+
+impl Variables {
+    pub fn new(unset: Value) -> Self {
+        Variables {
+            values: FnvHashMap::default(),
+            unset,
+        }
+    }
+
+    /// Change the value of variables that have not been stored yet.
+    pub fn set_unset(&mut self, unset: Value) {
+        self.unset = unset
+    }
+}
+
+impl Fork for Variables {
+    fn fork(&self) -> Self {
+        Variables {
+            values: self.values.iter().map(|(name, value)| (name.clone(), value.fork())).collect(),
+            unset: self.unset.fork(),
+        }
+    }
+}
+
+thread_local! {
+    static VARIABLES: RefCell<Option<Variables>> = const { RefCell::new(None) };
+}
+
+/// Keeps a fresh set of variables for the current thread until dropped.
+#[must_use]
+pub struct VariablesGuard {
+    previous: Option<Variables>,
+}
+
+/// Start with no variables set, so that reading any variable gives `unset`.
+pub fn start_variables(unset: Value) -> VariablesGuard {
+    let previous = VARIABLES.with(|cell| cell.replace(Some(Variables::new(unset))));
+    VariablesGuard { previous }
+}
+
+/// Run with the given variables active instead of fresh ones, and keep any changes to them.
+pub fn with_variables<T>(variables: &mut Variables, run: impl FnOnce() -> T) -> T {
+    let previous = VARIABLES.with(|cell| cell.replace(Some(mem::take(variables))));
+    let res = run();
+    *variables = VARIABLES.with(|cell| cell.replace(previous))
+        .expect("variables were removed while running");
+    res
+}
+
+impl Drop for VariablesGuard {
+    fn drop(&mut self) {
+        VARIABLES.with(|cell| cell.replace(self.previous.take()));
+    }
+}
+
+/// Set the variable, replacing any previous value. Does nothing if there are no active variables.
+pub fn store_var(name: &str, value: Value) {
+    VARIABLES.with(|cell| {
+        if let Some(variables) = cell.borrow_mut().as_mut() {
+            variables.values.insert(name.to_owned(), value);
+        }
+    })
+}
+
+/// Get the variable, or the unset value if it was never stored (or zero if there are no active variables).
+pub fn load_var(name: &str) -> Value {
+    VARIABLES.with(|cell| match cell.borrow().as_ref() {
+        Some(variables) => variables.values.get(name).unwrap_or(&variables.unset).fork(),
+        None => Value::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Nr;
+    use crate::Text;
+
+    use super::*;
+
+    #[test]
+    fn unset_gives_input() {
+        let _vars = start_variables(Value::Txt(Text::of("input")));
+        assert_eq!(load_var("x"), Value::Txt(Text::of("input")));
+        store_var("x", Value::Num(Nr::new(1.0)));
+        assert_eq!(load_var("x"), Value::Num(Nr::new(1.0)));
+        assert_eq!(load_var("y"), Value::Txt(Text::of("input")));
+    }
+
+    #[test]
+    fn kept_between_runs() {
+        let mut variables = Variables::new(Value::Num(Nr::new(7.0)));
+        with_variables(&mut variables, || store_var("x", Value::Num(Nr::new(1.0))));
+        assert_eq!(load_var("x"), Value::default());
+        with_variables(&mut variables, || {
+            assert_eq!(load_var("x"), Value::Num(Nr::new(1.0)));
+            assert_eq!(load_var("a"), Value::Num(Nr::new(7.0)));
+        });
+    }
+
+    #[test]
+    fn nested_start_restores_previous() {
+        let _outer = start_variables(Value::default());
+        store_var("x", Value::Num(Nr::new(1.0)));
+        {
+            let _inner = start_variables(Value::Num(Nr::new(2.0)));
+            assert_eq!(load_var("x"), Value::Num(Nr::new(2.0)));
+        }
+        assert_eq!(load_var("x"), Value::Num(Nr::new(1.0)));
+    }
+}
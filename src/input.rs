@@ -0,0 +1,307 @@
+use ::std::io;
+use ::std::io::BufRead;
+use ::std::io::Read;
+use ::std::iter;
+use ::std::iter::Peekable;
+use ::std::str::Chars;
+
+use crate::Array;
+use crate::ErrorKind;
+use crate::exec::fail_later;
+use crate::Nr;
+use crate::Text;
+use crate::tilde_log;
+use crate::TildeError;
+use crate::TildeRes;
+use crate::Value;
+
+/// How input text is turned into the initial value on the stack.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum InputMode {
+    /// An array with a text for each line, in the order they were read. Lines are read when they are needed.
+    #[default]
+    Lines,
+    /// An array with an element for each line, which is a number if the line is one, and a text otherwise.
+    /// Lines are read when they are needed.
+    Numbers,
+    /// All input as a single text.
+    Text,
+    /// An array with a text for each character.
+    Chars,
+    /// An array with a text for each whitespace-separated token.
+    Words,
+    /// A json document, where arrays, numbers and strings become arrays, numbers and texts.
+    Json,
+}
+
+impl InputMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "lines" => Some(InputMode::Lines),
+            "numbers" => Some(InputMode::Numbers),
+            "text" => Some(InputMode::Text),
+            "chars" => Some(InputMode::Chars),
+            "words" => Some(InputMode::Words),
+            "json" => Some(InputMode::Json),
+            _ => None,
+        }
+    }
+}
+
+pub fn build_input<R: io::Read + 'static>(reader: io::BufReader<R>, mode: InputMode) -> TildeRes<Value> {
+    Ok(match mode {
+        InputMode::Lines => Value::Arr(Array::lazy(read_lines(reader)
+            .map(|line| Value::Txt(Text::of(line))))),
+        InputMode::Numbers => Value::Arr(Array::lazy(read_lines(reader)
+            .map(|line| match parse_nr(line.trim()) {
+                Some(nr) => Value::Num(nr),
+                None => Value::Txt(Text::of(line)),
+            }))),
+        InputMode::Text => Value::Txt(Text::of(read_all(reader)?)),
+        InputMode::Chars => Value::Arr(Array::of(read_all(reader)?
+            .chars()
+            .map(|ch| Text::of(ch.to_string()))
+            .collect())),
+        InputMode::Words => Value::Arr(Array::of(read_all(reader)?
+            .split_whitespace()
+            .map(Text::of)
+            .collect())),
+        InputMode::Json => parse_json(&read_all(reader)?)?,
+    })
+}
+
+/// Read lines without line endings, one at a time. Input ends at the first read error, which is
+/// reported as soon as the running operation is done.
+fn read_lines<R: io::Read>(mut reader: io::BufReader<R>) -> impl Iterator<Item=String> {
+    iter::from_fn(move || {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => return None,
+            Ok(_) => {},
+            Err(err) => {
+                tilde_log!("stopped reading input lines, err: {err}");
+                fail_later(read_err(err));
+                return None
+            },
+        }
+        while line.ends_with('\n') || line.ends_with('\r') {
+            line.pop().unwrap();
+        }
+        Some(line)
+    })
+}
+
+fn read_all<R: io::Read>(mut reader: io::BufReader<R>) -> TildeRes<String> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text).map_err(read_err)?;
+    Ok(text)
+}
+
+fn read_err(err: io::Error) -> TildeError {
+    TildeError::io(format!("failed to read input, err: {err}"))
+}
+
+/// Parse a finite number, rejecting words like 'inf' and 'nan' that Rust accepts.
+fn parse_nr(text: &str) -> Option<Nr> {
+    match text.parse::<f64>() {
+        Ok(nr) if nr.is_finite() => Some(Nr::new(nr)),
+        _ => None,
+    }
+}
+
+fn input_err(msg: impl Into<String>) -> TildeError {
+    TildeError::new(ErrorKind::Input(msg.into()))
+}
+
+fn parse_json(text: &str) -> TildeRes<Value> {
+    let mut chars = text.chars().peekable();
+    let value = parse_json_value(&mut chars)?;
+    skip_whitespace(&mut chars);
+    match chars.next() {
+        None => Ok(value),
+        Some(ch) => Err(input_err(format!("unexpected '{ch}' after end of json input"))),
+    }
+}
+
+fn parse_json_value(chars: &mut Peekable<Chars>) -> TildeRes<Value> {
+    skip_whitespace(chars);
+    match chars.peek().copied() {
+        Some('[') => {
+            chars.next();
+            let mut items = Vec::new();
+            skip_whitespace(chars);
+            if chars.peek() == Some(&']') {
+                chars.next();
+                return Ok(Value::Arr(Array::of(items)))
+            }
+            loop {
+                items.push(parse_json_value(chars)?);
+                skip_whitespace(chars);
+                match chars.next() {
+                    Some(',') => continue,
+                    Some(']') => return Ok(Value::Arr(Array::of(items))),
+                    Some(ch) => return Err(input_err(format!("expected ',' or ']' in json array, found '{ch}'"))),
+                    None => return Err(input_err("json array was not closed")),
+                }
+            }
+        },
+        Some('"') => {
+            chars.next();
+            Ok(Value::Txt(Text::of(parse_json_str(chars)?)))
+        },
+        Some(ch) if ch == '-' || ch.is_ascii_digit() => {
+            let mut nr = String::new();
+            while let Some(ch) = chars.next_if(|ch| matches!(ch, '0'..='9' | '-' | '+' | '.' | 'e' | 'E')) {
+                nr.push(ch);
+            }
+            parse_nr(&nr).map(Value::Num).ok_or_else(|| input_err(format!("invalid number '{nr}' in json input")))
+        },
+        Some('{') => Err(input_err("json objects are not supported as input, only arrays, numbers and strings")),
+        Some('t' | 'f' | 'n') => Err(input_err("json true, false and null are not supported as input, only arrays, numbers and strings")),
+        Some(ch) => Err(input_err(format!("unexpected '{ch}' in json input"))),
+        None => Err(input_err("unexpected end of json input")),
+    }
+}
+
+/// Parse the rest of a json string, after the opening quote.
+fn parse_json_str(chars: &mut Peekable<Chars>) -> TildeRes<String> {
+    let mut text = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(text),
+            Some('\\') => match chars.next() {
+                Some('"') => text.push('"'),
+                Some('\\') => text.push('\\'),
+                Some('/') => text.push('/'),
+                Some('b') => text.push('\u{8}'),
+                Some('f') => text.push('\u{c}'),
+                Some('n') => text.push('\n'),
+                Some('r') => text.push('\r'),
+                Some('t') => text.push('\t'),
+                Some('u') => {
+                    let code = parse_json_hex(chars)?;
+                    let code = if (0xD800..0xDC00).contains(&code) {
+                        if chars.next() != Some('\\') || chars.next() != Some('u') {
+                            return Err(input_err("unpaired surrogate escape in json string"))
+                        }
+                        let low = parse_json_hex(chars)?;
+                        if !(0xDC00..0xE000).contains(&low) {
+                            return Err(input_err("unpaired surrogate escape in json string"))
+                        }
+                        0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00)
+                    } else {
+                        code
+                    };
+                    text.push(char::from_u32(code).ok_or_else(|| input_err("invalid unicode escape in json string"))?);
+                },
+                Some(ch) => return Err(input_err(format!("unknown escape '\\{ch}' in json string"))),
+                None => return Err(input_err("json string was not closed")),
+            },
+            Some(ch) => text.push(ch),
+            None => return Err(input_err("json string was not closed")),
+        }
+    }
+}
+
+fn parse_json_hex(chars: &mut Peekable<Chars>) -> TildeRes<u32> {
+    let mut code = 0;
+    for _ in 0..4 {
+        let digit = chars.next()
+            .and_then(|ch| ch.to_digit(16))
+            .ok_or_else(|| input_err("expected four hex digits after \\u in json string"))?;
+        code = code * 16 + digit;
+    }
+    Ok(code)
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exec::check_budget;
+
+    use super::*;
+
+    fn build(input: &str, mode: InputMode) -> TildeRes<Value> {
+        build_input(io::BufReader::new(io::Cursor::new(input.to_owned())), mode)
+    }
+
+    fn txt(text: &str) -> Value {
+        Value::Txt(Text::of(text))
+    }
+
+    fn num(nr: f64) -> Value {
+        Value::Num(Nr::new(nr))
+    }
+
+    #[test]
+    fn lines_in_order() {
+        assert_eq!(build("a\r\nb\n\nc", InputMode::Lines).unwrap(),
+            Value::Arr(Array::of(vec![txt("a"), txt("b"), txt(""), txt("c")])));
+    }
+
+    #[test]
+    fn lines_are_lazy() {
+        let Value::Arr(arr) = build("a\nb\nc\n", InputMode::Lines).unwrap() else {
+            panic!("expected array")
+        };
+        assert_eq!(format!("{arr:?}"), "[...]");
+        assert_eq!(arr.index(Nr::new(1.0)).unwrap(), txt("b"));
+        assert_eq!(format!("{arr:?}"), "[\"a\",\"b\",...]");
+    }
+
+    #[test]
+    fn read_error_is_reported() {
+        let reader = io::BufReader::new(io::Cursor::new(b"a\n\xff\n".to_vec()));
+        let Value::Arr(arr) = build_input(reader, InputMode::Lines).unwrap() else {
+            panic!("expected array")
+        };
+        assert_eq!(arr.len(), 1);
+        assert!(matches!(check_budget().unwrap_err().kind(), ErrorKind::Io(_)));
+    }
+
+    #[test]
+    fn numbers_where_possible() {
+        assert_eq!(build("1\n -2.5 \nx\ninf\n", InputMode::Numbers).unwrap(),
+            Value::Arr(Array::of(vec![num(1.0), num(-2.5), txt("x"), txt("inf")])));
+    }
+
+    #[test]
+    fn text_chars_words() {
+        assert_eq!(build("a b\n", InputMode::Text).unwrap(), txt("a b\n"));
+        assert_eq!(build("aé\n", InputMode::Chars).unwrap(),
+            Value::Arr(Array::of(vec![txt("a"), txt("é"), txt("\n")])));
+        assert_eq!(build(" a  b\n\tc ", InputMode::Words).unwrap(),
+            Value::Arr(Array::of(vec![txt("a"), txt("b"), txt("c")])));
+    }
+
+    #[test]
+    fn json_document() {
+        assert_eq!(build(" [1, -2e1, [\"a\\n\\u00e9\\ud83d\\ude00\"], []] ", InputMode::Json).unwrap(),
+            Value::Arr(Array::of(vec![
+                num(1.0),
+                num(-20.0),
+                Value::Arr(Array::of(vec![txt("a\né😀")])),
+                Value::Arr(Array::of(Vec::<Value>::new())),
+            ])));
+        assert_eq!(build("\"hi\"", InputMode::Json).unwrap(), txt("hi"));
+    }
+
+    #[test]
+    fn json_errors() {
+        assert!(matches!(build("{}", InputMode::Json).unwrap_err().kind(), ErrorKind::Input(_)));
+        assert!(matches!(build("[1, true]", InputMode::Json).unwrap_err().kind(), ErrorKind::Input(_)));
+        assert!(matches!(build("[1, 2", InputMode::Json).unwrap_err().kind(), ErrorKind::Input(_)));
+        assert!(matches!(build("1 2", InputMode::Json).unwrap_err().kind(), ErrorKind::Input(_)));
+        assert!(matches!(build("", InputMode::Json).unwrap_err().kind(), ErrorKind::Input(_)));
+    }
+
+    #[test]
+    fn json_unpaired_surrogates() {
+        for json in ["\"\\ud800\\u0041\"", "\"\\ud800\\ud800\"", "\"\\ud800x\"", "\"\\ude00\""] {
+            assert!(matches!(build(json, InputMode::Json).unwrap_err().kind(), ErrorKind::Input(_)), "{json}");
+        }
+    }
+}
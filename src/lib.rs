@@ -13,24 +13,34 @@
 
 extern crate core;
 
+use ::std::cell::RefCell;
 use ::std::io;
-use ::std::io::BufRead;
 use ::std::io::stdin;
-use ::std::io::Read;
 use ::std::io::Write;
-use ::std::sync::Arc;
-use ::std::sync::atomic::AtomicBool;
+use ::std::rc::Rc;
 use ::std::sync::atomic::Ordering;
-use ::std::thread;
-use ::std::thread::sleep;
-use ::std::time::Duration;
 
 use crate::common::escape_for_string;
 use crate::common::is_safe_for_string;
+pub use crate::common::ErrorKind;
 pub use crate::common::log as tilde_log;
+pub use crate::common::Span;
+pub use crate::common::TildeError;
+pub use crate::compile::DecodeError;
 use crate::compile::parse;
 use crate::dict::ALLOW_COMPRESSION;
+use crate::exec::check_budget;
 use crate::exec::execute;
+use crate::exec::execute_stack;
+use crate::exec::start_budget;
+use crate::exec::start_printing;
+use crate::input::build_input;
+use crate::exec::Stack;
+use crate::output::ends_with_newline;
+use crate::output::write_output;
+pub use crate::exec::ExecConfig;
+pub use crate::exec::MAX_DEPTH;
+pub use crate::exec::RUN_STACK_SIZE;
 
 pub use self::data::Array;
 pub use self::data::Func;
@@ -38,6 +48,9 @@ pub use self::data::Nr;
 pub use self::data::Text;
 pub use self::data::Value;
 pub use self::data::Values;
+pub use self::input::InputMode;
+pub use self::output::OutputMode;
+pub use self::repl::tilde_repl;
 
 // #[cfg(feature = "gen")]
 // pub use self::gen::mddoc::tilde_gen_md_docs;
@@ -52,27 +65,54 @@ mod data;
 //TODO @mark: ^ fix and enable `gen`
 mod op;
 mod dict;
+mod input;
+mod output;
+mod repl;
 
 #[cfg(not(feature = "gen"))]
 pub fn tilde_gen_md_docs() -> TildeRes<()> {
-    Err("doc-gen can only be used if compiled with feature `gen`".to_owned())
+    Err(TildeError::runtime("doc-gen can only be used if compiled with feature `gen`"))
 }
 
-pub type TildeRes<T> = Result<T, String>;
+pub type TildeRes<T> = Result<T, TildeError>;
 pub type UINT = u64;
 
+/// Run the command line operation. Running a program writes its output directly, other operations return
+/// a value to show.
 pub fn run_tilde(args: &TildeArgs) -> TildeRes<Value> {
+    run_tilde_with(args, stdin().lock(), io::stdout())
+}
+
+/// Like [run_tilde], but with the given reader and writer instead of stdin and stdout.
+pub fn run_tilde_with<R: io::BufRead + 'static, W: io::Write + 'static>(
+    args: &TildeArgs,
+    reader: R,
+    writer: W,
+) -> TildeRes<Value> {
     match &args.operation {
         CliOperation::Run(source, mode) => {
             ALLOW_COMPRESSION.store(false, Ordering::Release);
-            //tilde_from();
-            let inp = gather_input();
-            let res = tilde_strs_mode(source, &inp, *mode)?;
-            Ok(Value::Txt(Text::of(res)))
-            //TODO @mark: change to tilde_from ^
+            let out = SharedWriter::new(writer);
+            tilde_from(
+                source,
+                io::BufReader::new(reader),
+                io::BufWriter::new(out.clone()),
+                *mode,
+                &args.io,
+                &args.config)?;
+            if !ends_with_newline(args.io.output) {
+                writeln!(out.0.borrow_mut())
+                    .map_err(|err| TildeError::io(format!("failed to write output, err: {err}")))?;
+            }
+            Ok(Value::Txt(Text::empty()))
         },
         CliOperation::Analyze(source) =>
             Ok(tilde_analyze(source)?.into()),
+        CliOperation::Repl => {
+            // compression stays allowed, because \golf encodes the session
+            tilde_repl(reader, writer, &args.config)?;
+            Ok(Value::Txt(Text::empty()))
+        },
         CliOperation::DocGen => {
             //tilde_gen_md_docs()?;
             todo!();  //TODO @mark: ^ fix and enable `gen`
@@ -84,12 +124,24 @@ pub fn run_tilde(args: &TildeArgs) -> TildeRes<Value> {
 #[derive(Debug)]
 pub struct TildeArgs {
     pub operation: CliOperation,
+    pub config: ExecConfig,
+    pub io: IoConfig,
+}
+
+/// Settings for how input is given to a program and how its results are shown.
+#[derive(Debug, Clone, Default)]
+pub struct IoConfig {
+    pub input: InputMode,
+    pub output: OutputMode,
+    /// Extra arguments, which are put on the stack as an array of texts, below the input.
+    pub args: Option<Vec<String>>,
 }
 
 #[derive(Debug)]
 pub enum CliOperation {
     Run(String, RunMode),
     Analyze(String),
+    Repl,
     DocGen,
 }
 
@@ -105,52 +157,36 @@ impl RunMode {
     }
 }
 
-//TODO @mverleg: move this code?
-fn gather_input() -> String {
-    let is_ready = Arc::new(AtomicBool::new(false));
-    let is_ready_clone = is_ready.clone();
-    thread::spawn(move || {
-        sleep(Duration::from_secs(5));
-        if !is_ready_clone.load(Ordering::Acquire) {
-            eprintln!("waiting for input on stdin; stdin needs to be closed before tilde can start")
-        }
-    });
-    let mut inp = String::new();
-    stdin().read_to_string(&mut inp)
-        .expect("failed to read stdin");  //TODO @mark:
-    is_ready.store(true, Ordering::Release);
-    inp
-}
-
 /// Run tilde with input lines produces by a reader, and results handled by a writer.
-pub fn tilde_from<R: io::Read, W: io::Write>(
+/// Input lines are read when the program needs them, and output is written as it is produced.
+pub fn tilde_from<R: io::Read + 'static, W: io::Write + 'static>(
     code: &str,
     reader: io::BufReader<R>,
     writer: io::BufWriter<W>,
     mode: RunMode,
+    io_config: &IoConfig,
+    config: &ExecConfig,
 ) -> TildeRes<()> {
     let prog = parse(code, mode)?;
-    let val = execute(prog, build_input(reader))?;
-    let mut writer = writer;
-    tilde_log!("tilde result: {}", val);
-    write!(writer, "{}", val).unwrap();
-    Ok(())
-}
-
-fn build_input<R: io::Read>(mut reader: io::BufReader<R>) -> Value {
-    let mut lines = Vec::new();
-    let mut line = String::new();
-    while let Ok(siz) = reader.read_line(&mut line) {
-        if siz == 0 {
-            break
-        }
-        while line.ends_with('\n') || line.ends_with('\r') {
-            line.pop().unwrap();
-        }
-        lines.push(Value::Txt(Text::of(&line)));
-        line.clear();
+    let mut inputs = Vec::with_capacity(2);
+    if let Some(args) = &io_config.args {
+        inputs.push(Value::Arr(Array::of(args.iter().map(Text::of).collect())));
     }
-    Value::Arr(Array::of(lines))
+    inputs.push(build_input(reader, io_config.input)?);
+    let writer = Rc::new(RefCell::new(writer));
+    let stack = {
+        let _printer = start_printing(writer.clone(), io_config.output);
+        execute_stack(prog, inputs, config)?
+    };
+    tilde_log!("tilde result stack: {}", stack.as_debug_str());
+    // writing lazy arrays produces their elements, which is limited like execution
+    let _budget = start_budget(config);
+    let mut writer = writer.borrow_mut();
+    write_output(&mut *writer, &stack, io_config.output)
+        .and_then(|_| writer.flush())
+        .map_err(|err| TildeError::io(format!("failed to write output, err: {err}")))?;
+    // lazy input may only be read while writing the output
+    check_budget()
 }
 
 /// Run tilde with strings as input and output, useful for testing.
@@ -158,33 +194,75 @@ pub fn tilde_strs(
     code: &str,
     input: &str,
 ) -> TildeRes<String> {
-    tilde_strs_mode(code, input, RunMode::Any)
+    tilde_strs_mode(code, input, RunMode::Any, &IoConfig::default(), &ExecConfig::default())
 }
 
 pub fn tilde_strs_mode(
     code: &str,
     input: &str,
     mode: RunMode,
+    io_config: &IoConfig,
+    config: &ExecConfig,
 ) -> TildeRes<String> {
-    let mut output = vec![];
+    let out = SharedWriter::new(Vec::new());
     tilde_from(
         code,
-        io::BufReader::new(io::Cursor::new(input)),
-        io::BufWriter::new(io::Cursor::new(&mut output)),
-        mode)?;
-    String::from_utf8(output).map_err(|err| format!("output was not utf8, err: {err}"))
-}
-
-// /// Run a Tilde routine, taking a single Value as input and producing a single value
-// /// as output if successful, or failing with an error message if unsuccessful.
-// pub fn tilde_eval(
-//     code: &str,
-//     input: Value,
-// ) -> TildeRes<Value> {
-//     todo!()
-//     //TODO @mark:
-// }
-//TODO @mark: TEMPORARY! REMOVE THIS!
+        io::BufReader::new(io::Cursor::new(input.to_owned())),
+        io::BufWriter::new(out.clone()),
+        mode,
+        io_config,
+        config)?;
+    String::from_utf8(out.0.take()).map_err(|err| TildeError::io(format!("output was not utf8, err: {err}")))
+}
+
+/// Output that stays usable after the buffered writer that wraps it is gone.
+#[derive(Debug)]
+pub(crate) struct SharedWriter<W>(pub(crate) Rc<RefCell<W>>);
+
+impl <W> SharedWriter<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        SharedWriter(Rc::new(RefCell::new(writer)))
+    }
+}
+
+impl <W> Clone for SharedWriter<W> {
+    fn clone(&self) -> Self {
+        SharedWriter(self.0.clone())
+    }
+}
+
+impl <W: Write> Write for SharedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.borrow_mut().flush()
+    }
+}
+
+/// Run a Tilde routine, taking a single Value as input and producing the top of the final stack
+/// as output if successful (or zero if the stack is empty), or an error if unsuccessful.
+pub fn tilde_eval(
+    code: &str,
+    input: Value,
+    mode: RunMode,
+    config: &ExecConfig,
+) -> TildeRes<Value> {
+    let prog = parse(code, mode)?;
+    execute(prog, input, config)
+}
+
+/// Like [tilde_eval], but return the whole final stack, with the top value last.
+pub fn tilde_eval_stack(
+    code: &str,
+    input: Value,
+    mode: RunMode,
+    config: &ExecConfig,
+) -> TildeRes<Vec<Value>> {
+    let prog = parse(code, mode)?;
+    execute_stack(prog, vec![input], config)
+}
 
 /// Analyze the Tilde source code and report stats as json.
 pub fn tilde_analyze(source: &str) -> TildeRes<String> {
@@ -2,23 +2,33 @@
 
 use ::std::env;
 use ::std::fs::read_to_string;
+use ::std::mem;
 use ::std::mem::size_of;
+use ::std::panic;
 use ::std::process::ExitCode;
 use ::std::str::from_utf8;
+use ::std::thread;
+use ::std::time::Duration;
 
 use ::base64::Engine;
 use ::base64::engine::general_purpose::URL_SAFE_NO_PAD;
 
 use ::tilde::CliOperation;
+use ::tilde::ExecConfig;
+use ::tilde::InputMode;
+use ::tilde::IoConfig;
+use ::tilde::MAX_DEPTH;
+use ::tilde::OutputMode;
 use ::tilde::run_tilde;
+use ::tilde::RUN_STACK_SIZE;
 use ::tilde::RunMode;
 use ::tilde::tilde_log;
 use ::tilde::TildeArgs;
 
 fn main() -> ExitCode {
     assert!(size_of::<usize>() >= size_of::<u32>(), "due to indexing tricks, platforms with narrow pointers are not supported at this time");
-    let operation: CliOperation = match parse_operation(env::args().collect()) {
-        ArgParseRes::Lib(op) => op,
+    let args: TildeArgs = match parse_args(env::args().collect()) {
+        ArgParseRes::Lib(args) => args,
         ArgParseRes::GenHelp => {
             println!("{}", gen_help());
             return ExitCode::from(0)
@@ -28,28 +38,112 @@ fn main() -> ExitCode {
             return ExitCode::from(2)
         }
     };
-    let args = TildeArgs { operation };
-    let res = run_tilde(&args);
+    // deep recursion needs more native stack than the main thread has
+    let res = thread::scope(|scope| {
+        let runner = thread::Builder::new()
+            .stack_size(RUN_STACK_SIZE)
+            .spawn_scoped(scope, || run_tilde(&args).map(|value| value.to_string()))
+            .expect("could not start a thread to run the program");
+        runner.join().unwrap_or_else(|err| panic::resume_unwind(err))
+    });
     match res {
         Ok(value) => {
-            println!("{value}");
+            if !matches!(args.operation, CliOperation::Run(..)) {
+                println!("{value}");
+            }
             ExitCode::from(0)
         },
         Err(err) => {
             eprintln!("{err}");
+            if let CliOperation::Run(src, _) | CliOperation::Analyze(src) = &args.operation {
+                if let Some(snippet) = err.source_snippet(src) {
+                    eprintln!("{snippet}");
+                }
+            }
             ExitCode::from(1)
         },
     }
 }
 
-fn parse_operation(mut args: Vec<String>) -> ArgParseRes {
+fn parse_args(mut args: Vec<String>) -> ArgParseRes {
     use ArgParseRes::*;
     args.reverse();
     args.pop();
-    let arg1 = args.pop();
-    let cli_op = match arg1.as_deref() {
-        Some("-h") | Some("--help") => GenHelp,
-        Some("-f") | Some("--file") => {
+    let mut operation = None;
+    let mut config = ExecConfig::default();
+    let mut io = IoConfig::default();
+    while let Some(arg) = args.pop() {
+        match arg.as_str() {
+            "--max-steps" => {
+                let Some(Ok(max_steps)) = args.pop().map(|val| val.parse::<u64>()) else {
+                    return Err("argument --max-steps expects a positive integer number of steps".to_string())
+                };
+                config.max_steps = Some(max_steps);
+            },
+            "--trace" => config.trace = true,
+            "--" => {
+                args.reverse();
+                io.args = Some(mem::take(&mut args));
+            },
+            "--input" => {
+                let Some(mode) = args.pop().and_then(|val| InputMode::from_name(&val)) else {
+                    return Err("argument --input expects one of: lines, numbers, text, chars, words, json".to_string())
+                };
+                io.input = mode;
+            },
+            "--output" => {
+                let Some(mode) = args.pop().and_then(|val| OutputMode::from_name(&val)) else {
+                    return Err("argument --output expects one of: top, stack, lines, json".to_string())
+                };
+                io.output = mode;
+            },
+            "--max-size" => {
+                let Some(Ok(max_size)) = args.pop().map(|val| val.parse::<u64>()) else {
+                    return Err("argument --max-size expects a positive integer number of array elements or text bytes".to_string())
+                };
+                config.max_size = Some(max_size);
+            },
+            "--max-depth" => {
+                let Some(Ok(max_depth)) = args.pop().map(|val| val.parse::<usize>()) else {
+                    return Err("argument --max-depth expects a positive integer number of nested recursive calls".to_string())
+                };
+                if max_depth > MAX_DEPTH {
+                    return Err(format!("argument --max-depth cannot be more than {MAX_DEPTH}, because deeper recursion does not fit on the stack"))
+                }
+                config.max_depth = Some(max_depth);
+            },
+            "--timeout" => {
+                let Some(Ok(timeout)) = args.pop().map(|val| val.parse::<f64>()) else {
+                    return Err("argument --timeout expects a number of seconds".to_string())
+                };
+                let Ok(timeout) = Duration::try_from_secs_f64(timeout) else {
+                    return Err("argument --timeout expects a non-negative number of seconds".to_string())
+                };
+                config.timeout = Some(timeout);
+            },
+            _ => {
+                if operation.is_some() {
+                    return Err(format!("cannot handle these extra arguments: {}\ntry --help for options", arg))
+                }
+                match parse_operation(arg, &mut args) {
+                    Lib(op) => operation = Some(op),
+                    GenHelp => return GenHelp,
+                    Err(err) => return Err(err),
+                }
+            },
+        }
+    }
+    match operation {
+        Some(operation) => Lib(TildeArgs { operation, config, io }),
+        None => Err("expected at least one argument; try --help for options".to_string()),
+    }
+}
+
+fn parse_operation(arg1: String, args: &mut Vec<String>) -> ArgParseRes<CliOperation> {
+    use ArgParseRes::*;
+    match arg1.as_str() {
+        "-h" | "--help" => GenHelp,
+        "-f" | "--file" => {
             let Some(pth) = args.pop() else {
                 return Err("argument -f/--file expects a path to a source file".to_string())
             };
@@ -60,21 +154,21 @@ fn parse_operation(mut args: Vec<String>) -> ArgParseRes {
             };
             Lib(CliOperation::Run(src, RunMode::Any))
         },
-        Some("-s") | Some("--source") => {
+        "-s" | "--source" => {
             let Some(src) = args.pop() else {
                 return Err("argument -s/--source expects a single argument containing source code".to_string())
             };
             tilde_log!("getting source from command line (length in utf8 bytes: {})", src.len());
             Lib(CliOperation::Run(src, RunMode::Any))
         },
-        Some("-g") | Some("--golf-source") => {
+        "-g" | "--golf-source" => {
             let Some(src) = args.pop() else {
                 return Err("argument -g/--golf-source expects a single argument containing source code with golf-character only".to_string())
             };
             tilde_log!("getting golf-only source from command line (length in utf8 bytes: {})", src.len());
             Lib(CliOperation::Run(src, RunMode::GolfOnly))
         },
-        Some("--base64source") => {
+        "--base64source" => {
             let Some(b64src) = args.pop() else {
                 return Err("argument --base64source expects a single argument containing base64-encoded source code".to_string())
             };
@@ -91,7 +185,7 @@ fn parse_operation(mut args: Vec<String>) -> ArgParseRes {
             };
             Lib(CliOperation::Run(src, RunMode::GolfOnly))
         },
-        Some("-F") | Some("--analyze-file") => {
+        "-F" | "--analyze-file" => {
             let Some(pth) = args.pop() else {
                 return Err("argument -F/--analyze-file expects a path to a source file".to_string())
             };
@@ -102,24 +196,20 @@ fn parse_operation(mut args: Vec<String>) -> ArgParseRes {
             };
             Lib(CliOperation::Analyze(src))
         },
-        Some("-S") | Some("--analyze-source") => {
+        "-S" | "--analyze-source" => {
             let Some(src) = args.pop() else {
                 return Err("argument -S/--analyze-source expects a single argument containing source code".to_string())
             };
             tilde_log!("getting source from command line (length in utf8 bytes: {}) for analysis", src.len());
             Lib(CliOperation::Analyze(src))
         },
-        Some("doc-gen") => Lib(CliOperation::DocGen),
-        Some(arg) => {
+        "--repl" => Lib(CliOperation::Repl),
+        "doc-gen" => Lib(CliOperation::DocGen),
+        arg => {
             let hint = if arg.contains('=') { "hint: --arg=value syntax is not supported, use '--arg value'\n" } else { "" };
-            return Err(format!("unknown argument '{arg}'\n{hint}try --help for options"))
+            Err(format!("unknown argument '{arg}'\n{hint}try --help for options"))
         },
-        None => Err("expected at least one argument; try --help for options".to_string()),
-    };
-    if !args.is_empty() {
-        return Err(format!("cannot handle these extra arguments: {}\ntry --help for options", args.join(" ")));
     }
-    cli_op
 }
 
 fn gen_help() -> String {
@@ -132,6 +222,7 @@ fn gen_help() -> String {
         format!("    {} -s 'source here'", env!("CARGO_PKG_NAME")),
         format!("    {} --file ./source.~", env!("CARGO_PKG_NAME")),
         format!("    cat input.txt | {} -s 'source here'", env!("CARGO_PKG_NAME")),
+        format!("    {} -s 'source here' -- arg1 arg2", env!("CARGO_PKG_NAME")),
         //TODO @mverleg: put better example source ^
         "".to_owned(),
         "OPTIONS:".to_owned(),
@@ -142,6 +233,15 @@ fn gen_help() -> String {
         "        --base64source B  Run base64-encoded source".to_owned(),
         "    -S, --analyze-source  Show information about the source string instead of running it".to_owned(),
         "    -F, --analyze-file    Show information about the source file instead of running it".to_owned(),
+        "        --repl            Run code line by line interactively, keeping the stack between lines".to_owned(),
+        "        --input M         Read stdin as lines (default), numbers, text, chars, words or json".to_owned(),
+        "        --output M        Print the top value (top, default), whole stack (stack), array lines (lines) or json".to_owned(),
+        "        -- A...           Put arguments A as an array of texts on the stack, below the input".to_owned(),
+        "        --trace           Print every operation and the stack before and after it to stderr".to_owned(),
+        "        --max-steps N     Stop with an error after running N operations".to_owned(),
+        "        --timeout S       Stop with an error if running takes longer than S seconds".to_owned(),
+        "        --max-size N      Stop with an error after creating N array elements or text bytes in total".to_owned(),
+        "        --max-depth N     Stop with an error if recursion through fix is nested more than N levels deep (at most 2000)".to_owned(),
         // "    --stats           Show stats (json) about the program instead of running it"
         //     .to_owned(),
     ];
@@ -152,8 +252,8 @@ fn gen_help() -> String {
 }
 
 #[derive(Debug)]
-enum ArgParseRes {
-    Lib(CliOperation),
+enum ArgParseRes<T = TildeArgs> {
+    Lib(T),
     Err(String),
     GenHelp,
 }
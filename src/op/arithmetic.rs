@@ -3,15 +3,19 @@ use ::std::borrow::Cow;
 
 use crate::Array;
 use crate::compile::GolfWord;
+use crate::compile::Letter;
 use crate::data::Fork;
 use crate::exec::BinaryExecutor;
+use crate::exec::dispatch_unary;
 use crate::exec::Executor;
 use crate::exec::UnaryExecutor;
+use crate::exec::unsupported;
 use crate::Nr;
 use crate::op::Op;
 use crate::op::OpTyp;
 use crate::op::text::Concat;
 use crate::Text;
+use crate::TildeRes;
 use crate::Value;
 use crate::Values;
 use crate::values;
@@ -49,37 +53,37 @@ impl OpTyp for Plus {
 }
 
 impl BinaryExecutor for Plus {
-    fn exec_nn(&self, deep: Nr, top: Nr) -> Values {
-        values![deep.plus(top)]
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        Ok(values![deep.plus(top)])
     }
 
-    fn exec_nt(&self, deep: Nr, top: Text) -> Values {
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
         match top.as_str().parse::<Nr>() {
             Ok(nr) => self.exec_nn(deep, nr),
             Err(_) => Concat.exec_nt(deep, top),
         }
     }
 
-    fn exec_na(&self, deep: Nr, top: Array) -> Values {
-        todo!()
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["number", "array"])
     }
 
-    fn exec_tn(&self, deep: Text, top: Nr) -> Values {
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
         match deep.as_str().parse::<Nr>() {
             Ok(nr) => self.exec_nn(nr, top),
             Err(_) => Concat.exec_tn(deep, top),
         }
     }
 
-    fn exec_tt(&self, deep: Text, top: Text) -> Values {
-        todo!()
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["text", "text"])
     }
 
-    fn exec_ta(&self, deep: Text, top: Array) -> Values {
-        todo!()
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["text", "array"])
     }
 
-    fn exec_an(&self, deep: Array, top: Nr) -> Values {
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
         let mut new = Vec::new();
         for item in deep.iter() {
             //TODO @mark: this flattens results if there are more than 1, is that correct? or should they be nested arrays?
@@ -88,33 +92,33 @@ impl BinaryExecutor for Plus {
                 Value::Txt(item) => self.exec_tn(item.fork(), top),
                 Value::Arr(item) => self.exec_an(item.fork(), top),
                 Value::Func(item) => self.exec_fn(item.fork(), top),
-            })
+            }?)
         }
-        values![Array::of(new)]
+        Ok(values![Array::of(new)])
     }
 
-    fn exec_at(&self, deep: Array, top: Text) -> Values {
-        todo!()
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["array", "text"])
     }
 
-    fn exec_aa(&self, deep: Array, top: Array) -> Values {
-        todo!()
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["array", "array"])
     }
 
-    fn exec_single_n(&self, single: Nr) -> Values {
-        todo!()
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_single_t(&self, single: Text) -> Values {
-        todo!()
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
     }
 
-    fn exec_single_a(&self, single: Array) -> Values {
-        todo!()
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -151,56 +155,56 @@ impl OpTyp for Minus {
 }
 
 impl BinaryExecutor for Minus {
-    fn exec_nn(&self, deep: Nr, top: Nr) -> Values {
-        values![deep.minus(top)]
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        Ok(values![deep.minus(top)])
     }
 
-    fn exec_nt(&self, deep: Nr, top: Text) -> Values {
-        todo!()
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["number", "text"])
     }
 
-    fn exec_na(&self, deep: Nr, top: Array) -> Values {
-        todo!()
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["number", "array"])
     }
 
-    fn exec_tn(&self, deep: Text, top: Nr) -> Values {
-        todo!()
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["text", "number"])
     }
 
-    fn exec_tt(&self, deep: Text, top: Text) -> Values {
-        todo!()
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["text", "text"])
     }
 
-    fn exec_ta(&self, deep: Text, top: Array) -> Values {
-        todo!()
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["text", "array"])
     }
 
-    fn exec_an(&self, deep: Array, top: Nr) -> Values {
-        todo!()
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["array", "number"])
     }
 
-    fn exec_at(&self, deep: Array, top: Text) -> Values {
-        todo!()
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["array", "text"])
     }
 
-    fn exec_aa(&self, deep: Array, top: Array) -> Values {
-        todo!()
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["array", "array"])
     }
 
-    fn exec_single_n(&self, single: Nr) -> Values {
-        todo!()
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_single_t(&self, single: Text) -> Values {
-        todo!()
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
     }
 
-    fn exec_single_a(&self, single: Array) -> Values {
-        todo!()
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -209,7 +213,7 @@ pub struct Mul;
 
 impl Mul {
     pub fn new() -> Op {
-        Op::of(Minus)
+        Op::of(Mul)
     }
 }
 
@@ -237,56 +241,65 @@ impl OpTyp for Mul {
 }
 
 impl BinaryExecutor for Mul {
-    fn exec_nn(&self, deep: Nr, top: Nr) -> Values {
-        values![deep.mul(top)]
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        Ok(values![deep.mul(top)])
     }
 
-    fn exec_nt(&self, deep: Nr, top: Text) -> Values {
-        todo!()
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        match top.as_str().parse::<Nr>() {
+            Ok(nr) => self.exec_nn(deep, nr),
+            Err(_) => unsupported(self, &["number", "text"]),
+        }
     }
 
-    fn exec_na(&self, deep: Nr, top: Array) -> Values {
-        todo!()
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["number", "array"])
     }
 
-    fn exec_tn(&self, deep: Text, top: Nr) -> Values {
-        todo!()
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        match deep.as_str().parse::<Nr>() {
+            Ok(nr) => self.exec_nn(nr, top),
+            Err(_) => unsupported(self, &["text", "number"]),
+        }
     }
 
-    fn exec_tt(&self, deep: Text, top: Text) -> Values {
-        todo!()
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        match (deep.as_str().parse::<Nr>(), top.as_str().parse::<Nr>()) {
+            (Ok(deep), Ok(top)) => self.exec_nn(deep, top),
+            _ => unsupported(self, &["text", "text"]),
+        }
     }
 
-    fn exec_ta(&self, deep: Text, top: Array) -> Values {
-        todo!()
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["text", "array"])
     }
 
-    fn exec_an(&self, deep: Array, top: Nr) -> Values {
-        todo!()
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["array", "number"])
     }
 
-    fn exec_at(&self, deep: Array, top: Text) -> Values {
-        todo!()
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["array", "text"])
     }
 
-    fn exec_aa(&self, deep: Array, top: Array) -> Values {
-        todo!()
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["array", "array"])
     }
 
-    fn exec_single_n(&self, single: Nr) -> Values {
-        todo!()
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_single_t(&self, single: Text) -> Values {
-        todo!()
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
     }
 
-    fn exec_single_a(&self, single: Array) -> Values {
-        todo!()
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -323,56 +336,56 @@ impl OpTyp for Div {
 }
 
 impl BinaryExecutor for Div {
-    fn exec_nn(&self, deep: Nr, top: Nr) -> Values {
-        values![deep.div(top)]
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        Ok(values![deep.div(top)])
     }
 
-    fn exec_nt(&self, deep: Nr, top: Text) -> Values {
-        todo!()
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["number", "text"])
     }
 
-    fn exec_na(&self, deep: Nr, top: Array) -> Values {
-        todo!()
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["number", "array"])
     }
 
-    fn exec_tn(&self, deep: Text, top: Nr) -> Values {
-        todo!()
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["text", "number"])
     }
 
-    fn exec_tt(&self, deep: Text, top: Text) -> Values {
-        todo!()
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["text", "text"])
     }
 
-    fn exec_ta(&self, deep: Text, top: Array) -> Values {
-        todo!()
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["text", "array"])
     }
 
-    fn exec_an(&self, deep: Array, top: Nr) -> Values {
-        todo!()
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["array", "number"])
     }
 
-    fn exec_at(&self, deep: Array, top: Text) -> Values {
-        todo!()
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["array", "text"])
     }
 
-    fn exec_aa(&self, deep: Array, top: Array) -> Values {
-        todo!()
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["array", "array"])
     }
 
-    fn exec_single_n(&self, single: Nr) -> Values {
-        todo!()
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_single_t(&self, single: Text) -> Values {
-        todo!()
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
     }
 
-    fn exec_single_a(&self, single: Array) -> Values {
-        todo!()
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -404,7 +417,61 @@ impl OpTyp for IntDiv {
     }
 
     fn as_executor(&self) -> Executor {
-        todo!()  //TODO @mark: TEMPORARY! REMOVE THIS!
+        Executor::Binary(self)
+    }
+}
+
+impl BinaryExecutor for IntDiv {
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        Ok(values![deep.div(top).floor()])
+    }
+
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["number", "text"])
+    }
+
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["number", "array"])
+    }
+
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["text", "number"])
+    }
+
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["text", "text"])
+    }
+
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["text", "array"])
+    }
+
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["array", "number"])
+    }
+
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["array", "text"])
+    }
+
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["array", "array"])
+    }
+
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
+    }
+
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
+    }
+
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -441,19 +508,367 @@ impl OpTyp for Sqrt {
 }
 
 impl UnaryExecutor for Sqrt {
-    fn exec_n(&self, value: Nr) -> Values {
-        values![Value::Num(value.abs_sqrt())]
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        Ok(values![Value::Num(value.abs_sqrt())])
+    }
+
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
+    }
+
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnaryFn {
+    Neg,
+    Abs,
+    Incr,
+    Decr,
+    Sign,
+    Reciprocal,
+    Floor,
+    Ceil,
+    Round,
+    Trunc,
+}
+
+/// Math operations on a single number. Texts that contain a number are converted, and arrays are
+/// handled element by element.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnaryMath(UnaryFn);
+
+impl UnaryMath {
+    pub fn new(func: UnaryFn) -> Op {
+        Op::of(UnaryMath(func))
+    }
+}
+
+impl OpTyp for UnaryMath {
+
+    fn description(&self) -> &'static str {
+        match self.0 {
+            UnaryFn::Neg => "negate a number",
+            UnaryFn::Abs => "absolute value of a number",
+            UnaryFn::Incr => "increment a number by 1",
+            UnaryFn::Decr => "decrement a number by 1",
+            UnaryFn::Sign => "sign of a number: -1, 0 or 1",
+            UnaryFn::Reciprocal => "reciprocal of a number (1 divided by it)",
+            UnaryFn::Floor => "round a number down",
+            UnaryFn::Ceil => "round a number up",
+            UnaryFn::Round => "round a number to the nearest integer, with halves away from zero",
+            UnaryFn::Trunc => "round a number towards zero, dropping the fraction",
+        }
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed(match self.0 {
+            UnaryFn::Neg => "neg",
+            UnaryFn::Abs => "abs",
+            UnaryFn::Incr => "incr",
+            UnaryFn::Decr => "decr",
+            UnaryFn::Sign => "sign",
+            UnaryFn::Reciprocal => "recip",
+            UnaryFn::Floor => "floor",
+            UnaryFn::Ceil => "ceil",
+            UnaryFn::Round => "round",
+            UnaryFn::Trunc => "trunc",
+        })
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        use Letter::*;
+        Some(match self.0 {
+            UnaryFn::Neg => GolfWord::new2(Plus, Exclamation),
+            UnaryFn::Abs => GolfWord::new2(Plus, Question),
+            UnaryFn::Incr => GolfWord::new2(Plus, Hat),
+            UnaryFn::Decr => GolfWord::new2(Plus, Tilde),
+            UnaryFn::Sign => GolfWord::new3(Plus, Question, Exclamation),
+            UnaryFn::Reciprocal => GolfWord::new3(Plus, Exclamation, Hat),
+            UnaryFn::Floor => GolfWord::new3(Plus, Tilde, Hash),
+            UnaryFn::Ceil => GolfWord::new3(Plus, Hat, Hash),
+            UnaryFn::Round => GolfWord::new3(Plus, Question, Hash),
+            UnaryFn::Trunc => GolfWord::new3(Plus, Exclamation, Hash),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Unary(self)
+    }
+}
+
+impl UnaryExecutor for UnaryMath {
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        Ok(values![match self.0 {
+            UnaryFn::Neg => value.neg(),
+            UnaryFn::Abs => value.abs(),
+            UnaryFn::Incr => value.incr(),
+            UnaryFn::Decr => value.decr(),
+            UnaryFn::Sign => value.sign(),
+            UnaryFn::Reciprocal => value.reciprocal(),
+            UnaryFn::Floor => value.floor(),
+            UnaryFn::Ceil => value.ceil(),
+            UnaryFn::Round => value.round(),
+            UnaryFn::Trunc => value.trunc(),
+        }])
+    }
+
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
+        match value.as_str().parse::<Nr>() {
+            Ok(nr) => self.exec_n(nr),
+            Err(_) => unsupported(self, &["text"]),
+        }
+    }
+
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
+        let mut new = Vec::new();
+        for item in value.iter() {
+            new.extend(dispatch_unary(self, Some(item))?)
+        }
+        Ok(values![Array::of(new)])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryFn {
+    Pow,
+    Mod,
+    Rem,
+    RoundTo,
+}
+
+/// Math operations on two numbers. Texts that contain a number are converted, and arrays are
+/// handled element by element.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BinaryMath(BinaryFn);
+
+impl BinaryMath {
+    pub fn new(func: BinaryFn) -> Op {
+        Op::of(BinaryMath(func))
+    }
+}
+
+impl OpTyp for BinaryMath {
+
+    fn description(&self) -> &'static str {
+        match self.0 {
+            BinaryFn::Pow => "raise the deeper number to the power of the top one",
+            BinaryFn::Mod => "modulo, where the result has the sign of the divisor (top), so -1 mod 3 is 2",
+            BinaryFn::Rem => "remainder after division, where the result has the sign of the dividend (deeper), so -1 rem 3 is -1",
+            BinaryFn::RoundTo => "round the deeper number to the number of decimals on top, which can be negative to round to tens, hundreds etc",
+        }
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed(match self.0 {
+            BinaryFn::Pow => "pow",
+            BinaryFn::Mod => "mod",
+            BinaryFn::Rem => "rem",
+            BinaryFn::RoundTo => "round-to",
+        })
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        use Letter::*;
+        Some(match self.0 {
+            BinaryFn::Mod => GolfWord::new2(Plus, Hash),
+            BinaryFn::Rem => GolfWord::new3(Plus, Hash, Exclamation),
+            BinaryFn::Pow => GolfWord::new3(Plus, Hash, Hat),
+            BinaryFn::RoundTo => GolfWord::new3(Plus, Hash, Question),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Binary(self)
+    }
+}
+
+impl BinaryExecutor for BinaryMath {
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        Ok(values![match self.0 {
+            BinaryFn::Pow => deep.pow(top),
+            BinaryFn::Mod => deep.modulo_floor(top),
+            BinaryFn::Rem => deep.modulo_trunc(top),
+            BinaryFn::RoundTo => deep.round_to(top),
+        }])
+    }
+
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        match top.as_str().parse::<Nr>() {
+            Ok(nr) => self.exec_nn(deep, nr),
+            Err(_) => unsupported(self, &["number", "text"]),
+        }
+    }
+
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        self.exec_each(top, Value::Num(deep), false)
+    }
+
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        match deep.as_str().parse::<Nr>() {
+            Ok(nr) => self.exec_nn(nr, top),
+            Err(_) => unsupported(self, &["text", "number"]),
+        }
+    }
+
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        match (deep.as_str().parse::<Nr>(), top.as_str().parse::<Nr>()) {
+            (Ok(deep), Ok(top)) => self.exec_nn(deep, top),
+            _ => unsupported(self, &["text", "text"]),
+        }
+    }
+
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        self.exec_each(top, Value::Txt(deep), false)
+    }
+
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        self.exec_each(deep, Value::Num(top), true)
+    }
+
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        self.exec_each(deep, Value::Txt(top), true)
     }
 
-    fn exec_t(&self, value: Text) -> Values {
-        todo!()
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        self.exec_elemwise(deep, top)
     }
 
-    fn exec_a(&self, value: Array) -> Values {
-        todo!()
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
+    }
+
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile::parse;
+    use crate::compile::Prog;
+    use crate::op::all_non_literals;
+    use crate::op::testing::nrs;
+    use crate::RunMode;
+
+    use super::*;
+
+    #[test]
+    fn unary_numbers() {
+        let cases = [
+            (UnaryFn::Neg, 2.5),
+            (UnaryFn::Abs, 2.5),
+            (UnaryFn::Incr, -1.5),
+            (UnaryFn::Decr, -3.5),
+            (UnaryFn::Sign, -1.0),
+            (UnaryFn::Reciprocal, -0.4),
+        ];
+        for (func, expected) in cases {
+            assert_eq!(UnaryMath(func).exec_n(Nr::new(-2.5)).unwrap(), values![Nr::new(expected)], "{func:?}");
+        }
+        assert_eq!(UnaryMath(UnaryFn::Sign).exec_n(Nr::zero()).unwrap(), values![Nr::zero()]);
+        assert_eq!(UnaryMath(UnaryFn::Neg).exec_n(Nr::zero()).unwrap()[0].to_string(), "0");
+    }
+
+    #[test]
+    fn unary_text_and_array() {
+        assert_eq!(UnaryMath(UnaryFn::Incr).exec_t(Text::of("41")).unwrap(), values![Nr::new(42.0)]);
+        assert!(UnaryMath(UnaryFn::Incr).exec_t(Text::of("x")).is_err());
+        assert_eq!(UnaryMath(UnaryFn::Abs).exec_a(nrs(&[-1.0, 2.0])).unwrap(), values![nrs(&[1.0, 2.0])]);
+    }
+
+    #[test]
+    fn mul_numeric_text() {
+        assert_eq!(Mul.exec_tt(Text::of("4"), Text::of("3")).unwrap(), values![Nr::new(12.0)]);
+        assert_eq!(Mul.exec_nt(Nr::new(2.0), Text::of("3")).unwrap(), values![Nr::new(6.0)]);
+        assert!(Mul.exec_tn(Text::of("x"), Nr::new(2.0)).is_err());
+    }
+
+    #[test]
+    fn modulo_signs() {
+        let modulo = BinaryMath(BinaryFn::Mod);
+        let rem = BinaryMath(BinaryFn::Rem);
+        assert_eq!(modulo.exec_nn(Nr::new(7.0), Nr::new(3.0)).unwrap(), values![Nr::new(1.0)]);
+        assert_eq!(modulo.exec_nn(Nr::new(-1.0), Nr::new(3.0)).unwrap(), values![Nr::new(2.0)]);
+        assert_eq!(modulo.exec_nn(Nr::new(1.0), Nr::new(-3.0)).unwrap(), values![Nr::new(-2.0)]);
+        assert_eq!(rem.exec_nn(Nr::new(-1.0), Nr::new(3.0)).unwrap(), values![Nr::new(-1.0)]);
+        assert_eq!(rem.exec_nn(Nr::new(7.0), Nr::new(-3.0)).unwrap(), values![Nr::new(1.0)]);
+        assert_eq!(modulo.exec_nn(Nr::new(7.0), Nr::zero()).unwrap(), values![Nr::zero()]);
+    }
+
+    #[test]
+    fn rounding_negatives() {
+        let cases = [
+            (UnaryFn::Floor, [-3.0, 2.0, -3.0]),
+            (UnaryFn::Ceil, [-2.0, 3.0, -2.0]),
+            (UnaryFn::Round, [-3.0, 3.0, -2.0]),
+            (UnaryFn::Trunc, [-2.0, 2.0, -2.0]),
+        ];
+        for (func, expected) in cases {
+            let res = UnaryMath(func).exec_a(nrs(&[-2.5, 2.5, -2.25])).unwrap();
+            assert_eq!(res, values![nrs(&expected)], "{func:?}");
+        }
+    }
+
+    #[test]
+    fn round_to_decimals() {
+        let round_to = BinaryMath(BinaryFn::RoundTo);
+        assert_eq!(round_to.exec_nn(Nr::new(2.71828), Nr::new(2.0)).unwrap(), values![Nr::new(2.72)]);
+        assert_eq!(round_to.exec_nn(Nr::new(-1234.5), Nr::new(-2.0)).unwrap(), values![Nr::new(-1200.0)]);
+    }
+
+    #[test]
+    fn int_div_rounds_down() {
+        assert_eq!(IntDiv.exec_nn(Nr::new(-7.0), Nr::new(2.0)).unwrap(), values![Nr::new(-4.0)]);
+        assert_eq!(IntDiv.exec_nn(Nr::new(7.0), Nr::new(2.0)).unwrap(), values![Nr::new(3.0)]);
+    }
+
+    #[test]
+    fn binary_text_and_array() {
+        let pow = BinaryMath(BinaryFn::Pow);
+        assert_eq!(pow.exec_tn(Text::of("2"), Nr::new(10.0)).unwrap(), values![Nr::new(1024.0)]);
+        assert!(pow.exec_tt(Text::of("2"), Text::of("x")).is_err());
+        assert_eq!(pow.exec_an(nrs(&[2.0, 3.0]), Nr::new(2.0)).unwrap(), values![nrs(&[4.0, 9.0])]);
+        assert_eq!(pow.exec_na(Nr::new(2.0), nrs(&[2.0, 3.0])).unwrap(), values![nrs(&[4.0, 8.0])]);
+        let modulo = BinaryMath(BinaryFn::Mod);
+        assert_eq!(modulo.exec_aa(nrs(&[5.0, 7.0]), nrs(&[3.0, 4.0])).unwrap(), values![nrs(&[2.0, 3.0])]);
+    }
+
+    #[test]
+    fn math_golf_codes_unique() {
+        let ops = all_non_literals();
+        let math_ops = ops.iter().filter(|op| op.as_any().is::<UnaryMath>() || op.as_any().is::<BinaryMath>());
+        for op in math_ops {
+            let code = op.golf_code().unwrap_or_else(|| panic!("no golf code for {}", op.long_code()));
+            let same_count = ops.iter().filter(|other| other.golf_code().as_ref() == Some(&code)).count();
+            assert_eq!(same_count, 1, "duplicate golf code {code} for {}", op.long_code());
+            assert_eq!(parse(&code.to_string(), RunMode::GolfOnly).unwrap(), Prog::of(vec![op.clone()]));
+        }
     }
 }
@@ -1,6 +1,8 @@
 use ::std::any::Any;
 use ::std::borrow::Cow;
-use ::std::collections::HashSet;
+use ::std::collections::HashMap;
+use ::std::hash::BuildHasher;
+use ::std::hash::RandomState;
 
 use crate::Array;
 use crate::Text;
@@ -10,7 +12,9 @@ use crate::exec::dispatch_binary;
 use crate::exec::BinaryExecutor;
 use crate::exec::Executor;
 use crate::exec::UnaryExecutor;
+use crate::exec::unsupported;
 use crate::Nr;
+use crate::TildeRes;
 use crate::op::Op;
 use crate::op::Plus;
 use crate::op::OpTyp;
@@ -49,6 +53,10 @@ impl OpTyp for Last {
         self
     }
 
+    fn output_count(&self) -> usize {
+        2
+    }
+
     fn as_executor(&self) -> Executor {
         Executor::Unary(self)
     }
@@ -56,21 +64,21 @@ impl OpTyp for Last {
 
 impl UnaryExecutor for Last {
 
-    fn exec_n(&self, value: Nr) -> Values {
-        todo!()
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_t(&self, value: Text) -> Values {
-        todo!()
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
     }
 
-    fn exec_a(&self, value: Array) -> Values {
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
         let (rem, last) = value.tail();
-        values![Value::Arr(rem), last]
+        Ok(values![Value::Arr(rem), last])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -108,56 +116,56 @@ impl OpTyp for Lookup {
 
 impl BinaryExecutor for Lookup {
 
-    fn exec_nn(&self, deep: Nr, top: Nr) -> Values {
-        todo!()
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number", "number"])
     }
 
-    fn exec_nt(&self, deep: Nr, top: Text) -> Values {
-        todo!()
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["number", "text"])
     }
 
-    fn exec_na(&self, deep: Nr, top: Array) -> Values {
-        todo!()
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["number", "array"])
     }
 
-    fn exec_tn(&self, deep: Text, top: Nr) -> Values {
-        todo!()
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["text", "number"])
     }
 
-    fn exec_tt(&self, deep: Text, top: Text) -> Values {
-        todo!()
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["text", "text"])
     }
 
-    fn exec_ta(&self, deep: Text, top: Array) -> Values {
-        todo!()
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["text", "array"])
     }
 
-    fn exec_an(&self, deep: Array, top: Nr) -> Values {
-        values![deep.index(top)]
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        Ok(values![deep.index(top)?])
     }
 
-    fn exec_at(&self, deep: Array, top: Text) -> Values {
-        todo!()
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["array", "text"])
     }
 
-    fn exec_aa(&self, deep: Array, top: Array) -> Values {
-        todo!()
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["array", "array"])
     }
 
-    fn exec_single_n(&self, single: Nr) -> Values {
-        todo!()
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_single_t(&self, single: Text) -> Values {
-        todo!()
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
     }
 
-    fn exec_single_a(&self, single: Array) -> Values {
-        values![single.index(Nr::zero())]
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        Ok(values![single.index(Nr::zero())?])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -202,21 +210,21 @@ impl OpTyp for Split {
 
 impl UnaryExecutor for Split {
 
-    fn exec_n(&self, value: Nr) -> Values {
-        todo!()
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_t(&self, value: Text) -> Values {
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
         let words = Split::split_str(value.as_str());
-        values![Array::of(words)]
+        Ok(values![Array::of(words)])
     }
 
-    fn exec_a(&self, value: Array) -> Values {
-        todo!()
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -261,28 +269,32 @@ impl OpTyp for Unique {
 
 impl UnaryExecutor for Unique {
 
-    fn exec_n(&self, value: Nr) -> Values {
-        todo!()
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_t(&self, value: Text) -> Values {
-        todo!()
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
     }
 
-    fn exec_a(&self, value: Array) -> Values {
-        let mut seen = HashSet::with_capacity(value.len());
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
+        // keyed on the hash, because arrays are not safe map keys, with equal-hash values compared directly
+        let hasher = RandomState::new();
+        let mut seen: HashMap<u64, Vec<Value>> = HashMap::with_capacity(value.len());
         let mut result = Vec::with_capacity(value.len());
         for val in value.iter() {
-            if seen.insert(val.fork()) {
+            let same_hash = seen.entry(hasher.hash_one(&val)).or_default();
+            if !same_hash.contains(&val) {
                 //TODO @mark: ^ remove clone
-                result.push(val.fork())
+                same_hash.push(val.fork());
+                result.push(val)
             }
         }
-        values![Value::Arr(Array::of(result))]
+        Ok(values![Value::Arr(Array::of(result))])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -327,22 +339,22 @@ impl OpTyp for Count {
 
 impl UnaryExecutor for Count {
 
-    fn exec_n(&self, value: Nr) -> Values {
-        todo!()
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_t(&self, value: Text) -> Values {
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
         let len = value.len() as f64;
-        values![Value::Num(Nr::new(len))]
+        Ok(values![Value::Num(Nr::new(len))])
     }
 
-    fn exec_a(&self, value: Array) -> Values {
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
         let len = value.len() as f64;
-        values![Value::Num(Nr::new(len))]
+        Ok(values![Value::Num(Nr::new(len))])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -379,26 +391,26 @@ impl OpTyp for Sum {
 }
 
 impl UnaryExecutor for Sum {
-    fn exec_n(&self, value: Nr) -> Values {
-        todo!()
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_t(&self, value: Text) -> Values {
-        todo!()
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
     }
 
-    fn exec_a(&self, value: Array) -> Values {
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
         let mut total = Value::Txt(Text::empty());
         for item in value.iter() {
-            let mut res = dispatch_binary(&Plus, Some(total), Some(item.fork()));
+            let mut res = dispatch_binary(&Plus, Some(total), Some(item.fork()))?;
             total = res.pop().expect("plus did not yield result");
             assert!(res.is_empty());
         }
-        values![total]
+        Ok(values![total])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -444,28 +456,28 @@ impl OpTyp for Flatten {
 
 impl UnaryExecutor for Flatten {
 
-    fn exec_n(&self, value: Nr) -> Values {
-        todo!()
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_t(&self, value: Text) -> Values {
-        let parts = Split.exec_t(value);
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
+        let parts = Split.exec_t(value)?;
         let mut result = Values::with_capacity(parts.len());
         for part in parts {
             result.push(part)
         }
-        result
+        Ok(result)
     }
 
-    fn exec_a(&self, value: Array) -> Values {
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
         let mut result = Values::with_capacity(value.len());
         for part in value.iter() {
             result.push(part.fork())
         }
-        result
+        Ok(result)
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
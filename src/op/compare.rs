@@ -0,0 +1,309 @@
+use ::std::any::Any;
+use ::std::borrow::Cow;
+use ::std::cmp::Ordering;
+use ::std::iter;
+
+use crate::Array;
+use crate::compile::GolfWord;
+use crate::compile::Letter;
+use crate::data::Fork;
+use crate::exec::BinaryExecutor;
+use crate::exec::Executor;
+use crate::exec::unsupported;
+use crate::Nr;
+use crate::op::Op;
+use crate::op::OpTyp;
+use crate::Text;
+use crate::TildeError;
+use crate::TildeRes;
+use crate::Value;
+use crate::Values;
+use crate::values;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cmp {
+    Eq,
+    Neq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+/// Compare two values, giving 1 if the comparison holds and 0 otherwise. Numbers compare numerically and
+/// texts lexicographically. A text compared to a number is parsed as a number if possible, otherwise the
+/// number is compared as text. Arrays compare lexicographically by element, where a non-array compared
+/// to an array acts like an array with just that value. With `each`, arrays are instead compared
+/// element by element, giving an array of results.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Compare {
+    cmp: Cmp,
+    each: bool,
+}
+
+impl Compare {
+    pub fn new(cmp: Cmp) -> Op {
+        Op::of(Compare { cmp, each: false })
+    }
+
+    pub fn each(cmp: Cmp) -> Op {
+        Op::of(Compare { cmp, each: true })
+    }
+
+    fn holds(&self, ordering: Option<Ordering>) -> TildeRes<bool> {
+        if let Some(ordering) = ordering {
+            return Ok(match self.cmp {
+                Cmp::Eq => ordering == Ordering::Equal,
+                Cmp::Neq => ordering != Ordering::Equal,
+                Cmp::Lt => ordering == Ordering::Less,
+                Cmp::Lte => ordering != Ordering::Greater,
+                Cmp::Gt => ordering == Ordering::Greater,
+                Cmp::Gte => ordering != Ordering::Less,
+            })
+        }
+        match self.cmp {
+            Cmp::Eq => Ok(false),
+            Cmp::Neq => Ok(true),
+            _ => Err(TildeError::runtime(format!("operation '{}' cannot order functions", self.long_code()))),
+        }
+    }
+
+    fn compare(&self, deep: Value, top: Value) -> TildeRes<Values> {
+        let holds = self.holds(compare_values(&deep, &top))?;
+        Ok(values![Nr::new(if holds { 1.0 } else { 0.0 })])
+    }
+}
+
+/// Order of two values, or `None` if they contain functions that cannot be ordered.
+fn compare_values(deep: &Value, top: &Value) -> Option<Ordering> {
+    match (deep, top) {
+        (Value::Num(deep), Value::Num(top)) => Some(deep.cmp(top)),
+        (Value::Txt(deep), Value::Txt(top)) => Some(deep.as_str().cmp(top.as_str())),
+        (Value::Num(deep), Value::Txt(top)) => Some(compare_nr_text(*deep, top)),
+        (Value::Txt(deep), Value::Num(top)) => Some(compare_nr_text(*top, deep).reverse()),
+        (Value::Arr(deep), Value::Arr(top)) => compare_seqs(deep.iter(), top.iter()),
+        (Value::Arr(deep), top @ (Value::Num(_) | Value::Txt(_))) => compare_seqs(deep.iter(), iter::once(top.fork())),
+        (deep @ (Value::Num(_) | Value::Txt(_)), Value::Arr(top)) => compare_seqs(iter::once(deep.fork()), top.iter()),
+        _ => None,
+    }
+}
+
+fn compare_nr_text(nr: Nr, text: &Text) -> Ordering {
+    match text.as_str().parse::<Nr>() {
+        Ok(text_nr) => nr.cmp(&text_nr),
+        Err(_) => nr.to_string().as_str().cmp(text.as_str()),
+    }
+}
+
+fn compare_seqs(mut deep: impl Iterator<Item=Value>, mut top: impl Iterator<Item=Value>) -> Option<Ordering> {
+    loop {
+        match (deep.next(), top.next()) {
+            (None, None) => return Some(Ordering::Equal),
+            (None, Some(_)) => return Some(Ordering::Less),
+            (Some(_), None) => return Some(Ordering::Greater),
+            (Some(deep), Some(top)) => match compare_values(&deep, &top)? {
+                Ordering::Equal => continue,
+                ordering => return Some(ordering),
+            },
+        }
+    }
+}
+
+impl OpTyp for Compare {
+
+    fn description(&self) -> &'static str {
+        match (self.cmp, self.each) {
+            (Cmp::Eq, false) => "1 if the two values are equal, 0 otherwise",
+            (Cmp::Neq, false) => "1 if the two values are not equal, 0 otherwise",
+            (Cmp::Lt, false) => "1 if the deeper value is less than the top value, 0 otherwise",
+            (Cmp::Lte, false) => "1 if the deeper value is less than or equal to the top value, 0 otherwise",
+            (Cmp::Gt, false) => "1 if the deeper value is greater than the top value, 0 otherwise",
+            (Cmp::Gte, false) => "1 if the deeper value is greater than or equal to the top value, 0 otherwise",
+            (Cmp::Eq, true) => "like eq, but compare arrays element by element, giving an array of 1s and 0s",
+            (Cmp::Neq, true) => "like neq, but compare arrays element by element, giving an array of 1s and 0s",
+            (Cmp::Lt, true) => "like lt, but compare arrays element by element, giving an array of 1s and 0s",
+            (Cmp::Lte, true) => "like lte, but compare arrays element by element, giving an array of 1s and 0s",
+            (Cmp::Gt, true) => "like gt, but compare arrays element by element, giving an array of 1s and 0s",
+            (Cmp::Gte, true) => "like gte, but compare arrays element by element, giving an array of 1s and 0s",
+        }
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed(match (self.cmp, self.each) {
+            (Cmp::Eq, false) => "eq",
+            (Cmp::Neq, false) => "neq",
+            (Cmp::Lt, false) => "lt",
+            (Cmp::Lte, false) => "lte",
+            (Cmp::Gt, false) => "gt",
+            (Cmp::Gte, false) => "gte",
+            (Cmp::Eq, true) => "eq-each",
+            (Cmp::Neq, true) => "neq-each",
+            (Cmp::Lt, true) => "lt-each",
+            (Cmp::Lte, true) => "lte-each",
+            (Cmp::Gt, true) => "gt-each",
+            (Cmp::Gte, true) => "gte-each",
+        })
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        use Letter::*;
+        let (opener, modifier) = match self.cmp {
+            Cmp::Eq => (Colon, None),
+            Cmp::Neq => (Colon, Some(Exclamation)),
+            Cmp::Gt => (Right, None),
+            Cmp::Gte => (Right, Some(Question)),
+            Cmp::Lt => (Right, Some(Hat)),
+            Cmp::Lte => (Right, Some(Tilde)),
+        };
+        Some(match (modifier, self.each) {
+            (None, false) => GolfWord::new1(opener),
+            (None, true) => GolfWord::new2(opener, Hash),
+            (Some(modifier), false) => GolfWord::new2(opener, modifier),
+            (Some(modifier), true) => GolfWord::new3(opener, modifier, Hash),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Binary(self)
+    }
+}
+
+impl BinaryExecutor for Compare {
+
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        self.compare(Value::Num(deep), Value::Num(top))
+    }
+
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        self.compare(Value::Num(deep), Value::Txt(top))
+    }
+
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        if self.each {
+            return self.exec_each(top, Value::Num(deep), false)
+        }
+        self.compare(Value::Num(deep), Value::Arr(top))
+    }
+
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        self.compare(Value::Txt(deep), Value::Num(top))
+    }
+
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        self.compare(Value::Txt(deep), Value::Txt(top))
+    }
+
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        if self.each {
+            return self.exec_each(top, Value::Txt(deep), false)
+        }
+        self.compare(Value::Txt(deep), Value::Arr(top))
+    }
+
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        if self.each {
+            return self.exec_each(deep, Value::Num(top), true)
+        }
+        self.compare(Value::Arr(deep), Value::Num(top))
+    }
+
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        if self.each {
+            return self.exec_each(deep, Value::Txt(top), true)
+        }
+        self.compare(Value::Arr(deep), Value::Txt(top))
+    }
+
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        if self.each {
+            return self.exec_elemwise(deep, top)
+        }
+        self.compare(Value::Arr(deep), Value::Arr(top))
+    }
+
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
+    }
+
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
+    }
+
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::op::testing::arr;
+    use crate::op::testing::nr;
+    use crate::op::testing::nrs;
+    use crate::op::testing::run;
+
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(run("drop 1 2 lt").unwrap(), nr(1.0));
+        assert_eq!(run("drop 2 2 lte").unwrap(), nr(1.0));
+        assert_eq!(run("drop 2 2 gt").unwrap(), nr(0.0));
+        assert_eq!(run("drop 3 2 gte").unwrap(), nr(1.0));
+        assert_eq!(run("drop 2 2 eq").unwrap(), nr(1.0));
+        assert_eq!(run("drop 2 2 neq").unwrap(), nr(0.0));
+    }
+
+    #[test]
+    fn texts() {
+        assert_eq!(run("drop 'abc' 'abd' lt").unwrap(), nr(1.0));
+        assert_eq!(run("drop 'b' 'abc' gt").unwrap(), nr(1.0));
+        assert_eq!(run("drop 'a' 'a' eq").unwrap(), nr(1.0));
+    }
+
+    #[test]
+    fn number_and_text() {
+        assert_eq!(run("drop 2 '2' eq").unwrap(), nr(1.0));
+        assert_eq!(run("drop '10' 9 gt").unwrap(), nr(1.0));
+        // not a number, so 9 is compared as text
+        assert_eq!(run("drop 9 'x' lt").unwrap(), nr(1.0));
+        assert_eq!(run("drop 9 'x' eq").unwrap(), nr(0.0));
+    }
+
+    #[test]
+    fn whole_arrays() {
+        assert_eq!(compare_values(&arr(&[1.0, 2.0]), &arr(&[1.0, 2.0])), Some(Ordering::Equal));
+        assert_eq!(compare_values(&arr(&[1.0, 2.0]), &arr(&[1.0, 3.0])), Some(Ordering::Less));
+        assert_eq!(compare_values(&arr(&[1.0, 2.0]), &arr(&[1.0])), Some(Ordering::Greater));
+        assert_eq!(compare_values(&arr(&[4.0]), &nr(4.0)), Some(Ordering::Equal));
+    }
+
+    #[test]
+    fn arrays_each() {
+        let res = Compare { cmp: Cmp::Lt, each: true }.exec_aa(nrs(&[1.0, 5.0, 3.0]), nrs(&[2.0, 2.0, 3.0])).unwrap();
+        assert_eq!(res, values![nrs(&[1.0, 0.0, 0.0])]);
+        let res = Compare { cmp: Cmp::Eq, each: true }.exec_an(nrs(&[1.0, 2.0, 1.0]), Nr::new(1.0)).unwrap();
+        assert_eq!(res, values![nrs(&[1.0, 0.0, 1.0])]);
+        let res = Compare { cmp: Cmp::Gt, each: true }.exec_na(Nr::new(2.0), nrs(&[1.0, 2.0, 3.0])).unwrap();
+        assert_eq!(res, values![nrs(&[1.0, 0.0, 0.0])]);
+    }
+
+    #[test]
+    fn captured_in_closure() {
+        assert_eq!(run("drop 3 arg 2 gt apply").unwrap(), nr(1.0));
+    }
+
+    #[test]
+    fn functions_are_not_ordered() {
+        let func = Value::Func(crate::Func::new());
+        assert_eq!(Compare { cmp: Cmp::Eq, each: false }.compare(func.fork(), nr(1.0)).unwrap(), values![nr(0.0)]);
+        assert!(Compare { cmp: Cmp::Lt, each: false }.compare(func, nr(1.0)).is_err());
+    }
+}
@@ -0,0 +1,442 @@
+use ::std::any::Any;
+use ::std::borrow::Cow;
+
+use crate::Array;
+use crate::compile::GolfWord;
+use crate::data::Fork;
+use crate::exec::BinaryExecutor;
+use crate::exec::Executor;
+use crate::exec::take_step;
+use crate::exec::TernaryExecutor;
+use crate::exec::unsupported;
+use crate::Func;
+use crate::Nr;
+use crate::op::Op;
+use crate::op::OpTyp;
+use crate::Text;
+use crate::TildeError;
+use crate::TildeRes;
+use crate::Value;
+use crate::Values;
+use crate::values;
+
+/// Apply the function to the value, and use the top of its result as the next value.
+pub fn step_value(op: &(impl OpTyp + ?Sized), func: &Func, value: Value) -> TildeRes<Value> {
+    take_step()?;
+    func.run_on_single(value)?.pop().ok_or_else(|| TildeError::runtime(
+        format!("the function given to '{}' must leave a value on the stack", op.long_code())))
+}
+
+/// Run the operation with functions as branches or bodies, unless the value they work on is a function too.
+/// In that case the operation is added to that function, like other operations do, instead of treating
+/// the function as a (truthy) value.
+fn run_or_capture(op: &impl TernaryExecutor, deep: Value, middle: Value, top: Value) -> TildeRes<Values> {
+    match deep {
+        Value::Func(deep) => Ok(values![Value::Func(deep.with_tern_deep(op.clone_op(), middle, top))]),
+        deep => op.exec_values(deep, middle, top),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct If;
+
+impl If {
+    pub fn new() -> Op {
+        Op::of(If)
+    }
+}
+
+impl OpTyp for If {
+
+    fn description(&self) -> &'static str {
+        "take a value, a then-branch and an else-branch, and pick the then-branch if the value is truthy. A function branch is applied to the value, other branches replace it. Without else-branch, a falsy value is kept."
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed("if")
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Ternary(self)
+    }
+}
+
+impl If {
+    fn pick(&self, value: Value, branch: Value) -> TildeRes<Values> {
+        match branch {
+            Value::Func(func) => func.run_on_single(value),
+            branch => Ok(values![branch]),
+        }
+    }
+}
+
+impl TernaryExecutor for If {
+
+    fn exec_values(&self, deep: Value, middle: Value, top: Value) -> TildeRes<Values> {
+        if deep.is_truthy() {
+            self.pick(deep, middle)
+        } else {
+            self.pick(deep, top)
+        }
+    }
+
+    fn exec_top_f(&self, deep: Value, middle: Value, top: Func) -> TildeRes<Values> {
+        run_or_capture(self, deep, middle, Value::Func(top))
+    }
+
+    fn exec_middle_f(&self, deep: Value, middle: Func, top: Value) -> TildeRes<Values> {
+        run_or_capture(self, deep, Value::Func(middle), top)
+    }
+
+    fn exec_double(&self, deep: Value, top: Value) -> TildeRes<Values> {
+        if deep.is_truthy() {
+            self.pick(deep, top)
+        } else {
+            Ok(values![deep])
+        }
+    }
+
+    fn exec_single(&self, single: Value) -> TildeRes<Values> {
+        unsupported(self, &[single.type_name()])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct While;
+
+impl While {
+    pub fn new() -> Op {
+        Op::of(While)
+    }
+}
+
+impl OpTyp for While {
+
+    fn description(&self) -> &'static str {
+        "take a value, a condition function and a body function, and keep applying the body to the value while the condition gives a truthy result for it"
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed("while")
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Ternary(self)
+    }
+}
+
+impl TernaryExecutor for While {
+
+    fn exec_values(&self, deep: Value, middle: Value, top: Value) -> TildeRes<Values> {
+        let (Value::Func(cond), Value::Func(body)) = (&middle, &top) else {
+            return unsupported(self, &[deep.type_name(), middle.type_name(), top.type_name()])
+        };
+        let mut value = deep;
+        while step_value(self, cond, value.fork())?.is_truthy() {
+            value = step_value(self, body, value)?;
+        }
+        Ok(values![value])
+    }
+
+    fn exec_top_f(&self, deep: Value, middle: Value, top: Func) -> TildeRes<Values> {
+        run_or_capture(self, deep, middle, Value::Func(top))
+    }
+
+    fn exec_middle_f(&self, deep: Value, middle: Func, top: Value) -> TildeRes<Values> {
+        run_or_capture(self, deep, Value::Func(middle), top)
+    }
+
+    fn exec_double(&self, deep: Value, top: Value) -> TildeRes<Values> {
+        unsupported(self, &[deep.type_name(), top.type_name()])
+    }
+
+    fn exec_single(&self, single: Value) -> TildeRes<Values> {
+        unsupported(self, &[single.type_name()])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Times;
+
+impl Times {
+    pub fn new() -> Op {
+        Op::of(Times)
+    }
+}
+
+impl OpTyp for Times {
+
+    fn description(&self) -> &'static str {
+        "take a value, a count and a function, and apply the function to the value that many times. The count must be a whole number that is not negative."
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed("times")
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Ternary(self)
+    }
+}
+
+impl TernaryExecutor for Times {
+
+    fn exec_values(&self, deep: Value, middle: Value, top: Value) -> TildeRes<Values> {
+        let (Value::Num(count), Value::Func(body)) = (&middle, &top) else {
+            return unsupported(self, &[deep.type_name(), middle.type_name(), top.type_name()])
+        };
+        let mut value = deep;
+        for _ in 0..count.to_uint()? {
+            value = step_value(self, body, value)?;
+        }
+        Ok(values![value])
+    }
+
+    fn exec_top_f(&self, deep: Value, middle: Value, top: Func) -> TildeRes<Values> {
+        run_or_capture(self, deep, middle, Value::Func(top))
+    }
+
+    fn exec_double(&self, deep: Value, top: Value) -> TildeRes<Values> {
+        unsupported(self, &[deep.type_name(), top.type_name()])
+    }
+
+    fn exec_single(&self, single: Value) -> TildeRes<Values> {
+        unsupported(self, &[single.type_name()])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UntilStable;
+
+impl UntilStable {
+    pub fn new() -> Op {
+        Op::of(UntilStable)
+    }
+}
+
+impl OpTyp for UntilStable {
+
+    fn description(&self) -> &'static str {
+        "take a value and a function, and keep applying the function until the value stops changing"
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed("until-stable")
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Binary(self)
+    }
+}
+
+impl UntilStable {
+    fn run(&self, value: Value, func: Func) -> TildeRes<Values> {
+        let mut value = value;
+        loop {
+            let next = step_value(self, &func, value.fork())?;
+            if next == value {
+                return Ok(values![value])
+            }
+            value = next;
+        }
+    }
+}
+
+impl BinaryExecutor for UntilStable {
+
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number", "number"])
+    }
+
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["number", "text"])
+    }
+
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["number", "array"])
+    }
+
+    fn exec_nf(&self, deep: Nr, top: Func) -> TildeRes<Values> {
+        self.run(Value::Num(deep), top)
+    }
+
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["text", "number"])
+    }
+
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["text", "text"])
+    }
+
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["text", "array"])
+    }
+
+    fn exec_tf(&self, deep: Text, top: Func) -> TildeRes<Values> {
+        self.run(Value::Txt(deep), top)
+    }
+
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["array", "number"])
+    }
+
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["array", "text"])
+    }
+
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["array", "array"])
+    }
+
+    fn exec_af(&self, deep: Array, top: Func) -> TildeRes<Values> {
+        self.run(Value::Arr(deep), top)
+    }
+
+    fn exec_fn(&self, deep: Func, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["function", "number"])
+    }
+
+    fn exec_ft(&self, deep: Func, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["function", "text"])
+    }
+
+    fn exec_fa(&self, deep: Func, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["function", "array"])
+    }
+
+    fn exec_ff(&self, deep: Func, top: Func) -> TildeRes<Values> {
+        self.run(Value::Func(deep), top)
+    }
+
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
+    }
+
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
+    }
+
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
+    }
+
+    fn exec_single_f(&self, single: Func) -> TildeRes<Values> {
+        unsupported(self, &["function"])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ErrorKind;
+    use crate::ExecConfig;
+    use crate::op::testing::nr;
+    use crate::op::testing::run;
+    use crate::op::testing::run_with;
+
+    #[test]
+    fn if_picks_branch() {
+        assert_eq!(run("drop 3 { 1 plus } { 1 minus } if").unwrap(), nr(4.0));
+        assert_eq!(run("drop 3 3 minus { 1 plus } { 1 minus } if").unwrap(), nr(-1.0));
+        assert_eq!(run("drop 'a' 1 2 if").unwrap(), nr(1.0));
+        assert_eq!(run("drop '' 1 2 if").unwrap(), nr(2.0));
+    }
+
+    #[test]
+    fn if_without_else() {
+        assert_eq!(run("drop 2 2 minus { 1 plus } if").unwrap(), nr(0.0));
+        assert_eq!(run("drop 2 { 1 plus } if").unwrap(), nr(3.0));
+    }
+
+    #[test]
+    fn while_loop() {
+        assert_eq!(run("drop 1 { 128 minus } { 2 mul } while").unwrap(), nr(128.0));
+        assert_eq!(run("drop 1 { 1 minus } { 2 mul } while").unwrap(), nr(1.0));
+    }
+
+    #[test]
+    fn function_value_is_captured() {
+        assert_eq!(run("drop 3 arg 3 minus { 10 mul } { 5 } if apply").unwrap(), nr(5.0));
+        assert_eq!(run("drop 4 arg 3 minus { 10 mul } { 5 } if apply").unwrap(), nr(10.0));
+        assert_eq!(run("drop 3 arg 1 plus 2 { 2 mul } times apply").unwrap(), nr(16.0));
+    }
+
+    #[test]
+    fn times_loop() {
+        assert_eq!(run("drop 1 10 { 2 mul } times").unwrap(), nr(1024.0));
+        assert_eq!(run("drop 1 1 1 minus { 2 mul } times").unwrap(), nr(1.0));
+    }
+
+    #[test]
+    fn times_count_must_be_whole() {
+        assert!(run("drop 5 5 2 div { 2 mul } times").is_err());
+        assert!(run("drop 5 2 neg { 2 mul } times").is_err());
+    }
+
+    #[test]
+    fn until_stable() {
+        assert_eq!(run("drop 1000 { 2 int-div } until-stable").unwrap(), nr(0.0));
+    }
+
+    #[test]
+    fn runaway_loop_uses_budget() {
+        let config = ExecConfig { max_steps: Some(1000), ..ExecConfig::default() };
+        let err = run_with("drop 1 { } { } while", &config).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::StepLimit { .. }), "{err}");
+        let err = run_with("drop 1 { 1 plus } until-stable", &config).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::StepLimit { .. }), "{err}");
+    }
+
+    #[test]
+    fn body_must_leave_value() {
+        let err = run("drop 1 3 { drop } times").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Runtime(_)), "{err}");
+    }
+}
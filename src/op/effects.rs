@@ -0,0 +1,147 @@
+use ::std::any::Any;
+use ::std::borrow::Cow;
+
+use crate::Array;
+use crate::compile::GolfWord;
+use crate::data::Fork;
+use crate::exec::Executor;
+use crate::exec::print_value;
+use crate::exec::return_value;
+use crate::exec::UnaryExecutor;
+use crate::exec::unsupported;
+use crate::Nr;
+use crate::op::Op;
+use crate::op::OpTyp;
+use crate::Text;
+use crate::TildeRes;
+use crate::Value;
+use crate::Values;
+use crate::values;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Print;
+
+impl Print {
+    pub fn new() -> Op {
+        Op::of(Print)
+    }
+}
+
+impl OpTyp for Print {
+
+    fn description(&self) -> &'static str {
+        "write the top value to the output right away, formatted like the final output, and leave it on the stack"
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed("print")
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Unary(self)
+    }
+}
+
+impl UnaryExecutor for Print {
+
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        print_value(&Value::Num(value))?;
+        Ok(values![value])
+    }
+
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
+        print_value(&Value::Txt(value.clone()))?;
+        Ok(values![value])
+    }
+
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
+        print_value(&Value::Arr(value.fork()))?;
+        Ok(values![value])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Return;
+
+impl Return {
+    pub fn new() -> Op {
+        Op::of(Return)
+    }
+}
+
+impl OpTyp for Return {
+
+    fn description(&self) -> &'static str {
+        "stop the program right away, with the top value as the only result"
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed("return")
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Unary(self)
+    }
+}
+
+impl UnaryExecutor for Return {
+
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        Err(return_value(Some(Value::Num(value))))
+    }
+
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
+        Err(return_value(Some(Value::Txt(value))))
+    }
+
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
+        Err(return_value(Some(Value::Arr(value))))
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        Err(return_value(None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::op::testing::nr;
+    use crate::op::testing::run_stack;
+
+    #[test]
+    fn print_keeps_value() {
+        assert_eq!(run_stack("drop 1 print 2").unwrap(), vec![nr(1.0), nr(2.0)]);
+    }
+
+    #[test]
+    fn return_stops_early() {
+        assert_eq!(run_stack("drop 1 2 return 3 plus").unwrap(), vec![nr(2.0)]);
+        assert_eq!(run_stack("drop 1 drop return 3").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn return_from_closure() {
+        let res = run_stack("drop 1 { 2 plus return } { 3 plus } if 10 mul").unwrap();
+        assert_eq!(res, vec![nr(3.0)]);
+    }
+}
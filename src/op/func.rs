@@ -5,12 +5,15 @@ use crate::{Array, Value};
 use crate::compile::GolfWord;
 use crate::exec::BinaryExecutor;
 use crate::exec::Executor;
+use crate::exec::new_small_stack;
 use crate::exec::NullaryExecutor;
+use crate::exec::unsupported;
 use crate::Func;
 use crate::Nr;
 use crate::op::Op;
 use crate::op::OpTyp;
 use crate::Text;
+use crate::TildeRes;
 use crate::Values;
 use crate::values;
 
@@ -48,8 +51,8 @@ impl OpTyp for Arg {
 
 impl NullaryExecutor for Arg {
 
-    fn exec(&self) -> Values {
-        values![Value::Func(Func::new())]
+    fn exec(&self) -> TildeRes<Values> {
+        Ok(values![Value::Func(Func::new())])
     }
 }
 
@@ -88,90 +91,93 @@ impl OpTyp for Apply {
 impl BinaryExecutor for Apply {
     //TODO @mark: make this apply until not a function, and make another Op that does apply-once (or maybe just map and reduce and filter are enough?)
 
-    fn exec_nn(&self, deep: Nr, top: Nr) -> Values {
-        todo!()
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number", "number"])
     }
 
-    fn exec_nt(&self, deep: Nr, top: Text) -> Values {
-        todo!()
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["number", "text"])
     }
 
-    fn exec_na(&self, deep: Nr, top: Array) -> Values {
-        todo!()
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["number", "array"])
     }
 
-    fn exec_nf(&self, deep: Nr, top: Func) -> Values {
-        let stack = top.run_on_single(Value::Num(deep));
-        Values::from(stack)
+    fn exec_nf(&self, deep: Nr, top: Func) -> TildeRes<Values> {
+        let stack = top.run_on_single(Value::Num(deep))?;
+        Ok(Values::from(stack))
     }
 
-    fn exec_tn(&self, deep: Text, top: Nr) -> Values {
-        todo!()
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["text", "number"])
     }
 
-    fn exec_tt(&self, deep: Text, top: Text) -> Values {
-        todo!()
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["text", "text"])
     }
 
-    fn exec_ta(&self, deep: Text, top: Array) -> Values {
-        todo!()
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["text", "array"])
     }
 
-    fn exec_tf(&self, deep: Text, top: Func) -> Values {
-        let stack = top.run_on_single(Value::Txt(deep));
-        Values::from(stack)
+    fn exec_tf(&self, deep: Text, top: Func) -> TildeRes<Values> {
+        let stack = top.run_on_single(Value::Txt(deep))?;
+        Ok(Values::from(stack))
     }
 
-    fn exec_an(&self, deep: Array, top: Nr) -> Values {
-        todo!()
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["array", "number"])
     }
 
-    fn exec_at(&self, deep: Array, top: Text) -> Values {
-        todo!()
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["array", "text"])
     }
 
-    fn exec_aa(&self, deep: Array, top: Array) -> Values {
-        todo!()
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["array", "array"])
     }
 
-    fn exec_af(&self, deep: Array, top: Func) -> Values {
-        let stack = top.run_on_single(Value::Arr(deep));
-        Values::from(stack)
+    fn exec_af(&self, deep: Array, top: Func) -> TildeRes<Values> {
+        let stack = top.run_on_single(Value::Arr(deep))?;
+        Ok(Values::from(stack))
     }
 
-    fn exec_fn(&self, deep: Func, top: Nr) -> Values {
-        todo!()
+    fn exec_fn(&self, deep: Func, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["function", "number"])
     }
 
-    fn exec_ft(&self, deep: Func, top: Text) -> Values {
-        todo!()
+    fn exec_ft(&self, deep: Func, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["function", "text"])
     }
 
-    fn exec_fa(&self, deep: Func, top: Array) -> Values {
-        todo!()
+    fn exec_fa(&self, deep: Func, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["function", "array"])
     }
 
-    fn exec_ff(&self, deep: Func, top: Func) -> Values {
-        todo!()
+    fn exec_ff(&self, deep: Func, top: Func) -> TildeRes<Values> {
+        unsupported(self, &["function", "function"])
     }
 
-    fn exec_single_n(&self, single: Nr) -> Values {
-        todo!()
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_single_t(&self, single: Text) -> Values {
-        todo!()
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
     }
 
-    fn exec_single_a(&self, single: Array) -> Values {
-        todo!()
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
     }
 
-    fn exec_single_f(&self, single: Func) -> Values {
-        todo!()
+    fn exec_single_f(&self, single: Func) -> TildeRes<Values> {
+        // there is no argument, so the closure's operations get only their captured values
+        let mut stack = new_small_stack();
+        single.run_on_stack(&mut stack)?;
+        Ok(stack)
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
@@ -0,0 +1,413 @@
+use ::std::any::Any;
+use ::std::borrow::Cow;
+
+use crate::Array;
+use crate::compile::GolfWord;
+use crate::compile::Letter;
+use crate::data::Fork;
+use crate::exec::BinaryExecutor;
+use crate::exec::Executor;
+use crate::exec::fail_later;
+use crate::exec::new_small_stack;
+use crate::exec::take_step;
+use crate::exec::TernaryExecutor;
+use crate::exec::unsupported;
+use crate::Func;
+use crate::Nr;
+use crate::op::control::step_value;
+use crate::op::Op;
+use crate::op::OpTyp;
+use crate::Text;
+use crate::TildeError;
+use crate::TildeRes;
+use crate::Value;
+use crate::Values;
+use crate::values;
+
+/// The characters of a text as separate texts, so that they can be passed to a closure one by one.
+fn chars(text: &Text) -> Vec<Value> {
+    text.as_str().chars()
+        .map(|chr| Value::Txt(Text::of(chr.to_string())))
+        .collect()
+}
+
+/// Run the function on a stack with the accumulated value below the element, and use the top of its result.
+fn accumulate(op: &(impl OpTyp + ?Sized), func: &Func, acc: Value, elem: Value) -> TildeRes<Value> {
+    take_step()?;
+    let mut stack = new_small_stack();
+    stack.push(acc);
+    stack.push(elem);
+    func.run_on_stack(&mut stack)?;
+    stack.pop().ok_or_else(|| TildeError::runtime(
+        format!("the function given to '{}' must leave a value on the stack", op.long_code())))
+}
+
+/// Array of the results of running `each` on the elements, which are only produced when they are needed,
+/// so that huge or streaming arrays can be mapped. An error is reported after the current operation,
+/// and ends the array.
+fn lazy_results(
+    elems: impl Iterator<Item=Value> + 'static,
+    mut each: impl FnMut(Value) -> TildeRes<Vec<Value>> + 'static,
+) -> Array {
+    Array::lazy(elems
+        .map_while(move |elem| match each(elem) {
+            Ok(results) => Some(results),
+            Err(err) => {
+                fail_later(err);
+                None
+            },
+        })
+        .flatten())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HigherFn {
+    Map,
+    FlatMap,
+    Filter,
+    Reject,
+    Reduce,
+    Any,
+    All,
+    CountWhere,
+    FindFirst,
+}
+
+/// Run a function on each element of an array, or each character of a text. Except for `flat-map`, the
+/// top value that the function leaves is used, and it is an error if it leaves nothing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HigherOrder(HigherFn);
+
+impl HigherOrder {
+    pub fn new(fun: HigherFn) -> Op {
+        Op::of(HigherOrder(fun))
+    }
+
+    fn apply(&self, elems: impl Iterator<Item=Value> + 'static, func: &Func, is_text: bool) -> TildeRes<Values> {
+        let mut elems = elems;
+        Ok(match self.0 {
+            HigherFn::Map => {
+                let (op, func) = (self.clone(), func.fork());
+                values![lazy_results(elems, move |elem| Ok(vec![step_value(&op, &func, elem)?]))]
+            }
+            HigherFn::FlatMap => {
+                let func = func.fork();
+                values![lazy_results(elems, move |elem| {
+                    take_step()?;
+                    let mut new = Vec::new();
+                    for res in func.run_on_single(elem)? {
+                        match res {
+                            Value::Arr(arr) => new.extend(arr.iter()),
+                            res => new.push(res),
+                        }
+                    }
+                    Ok(new)
+                })]
+            }
+            HigherFn::Filter | HigherFn::Reject if !is_text => {
+                let keep_truthy = self.0 == HigherFn::Filter;
+                let (op, func) = (self.clone(), func.fork());
+                values![lazy_results(elems, move |elem| {
+                    let is_kept = step_value(&op, &func, elem.fork())?.is_truthy() == keep_truthy;
+                    Ok(if is_kept { vec![elem] } else { vec![] })
+                })]
+            }
+            HigherFn::Filter | HigherFn::Reject => {
+                let keep_truthy = self.0 == HigherFn::Filter;
+                let mut text = String::new();
+                for elem in elems {
+                    if step_value(self, func, elem.fork())?.is_truthy() == keep_truthy {
+                        if let Value::Txt(chr) = elem {
+                            text.push_str(chr.as_str());
+                        }
+                    }
+                }
+                values![Text::of(text)]
+            }
+            HigherFn::Reduce => {
+                let Some(mut acc) = elems.next() else {
+                    return Err(TildeError::runtime(format!(
+                        "cannot '{}' nothing, use 'fold' to give a starting value", self.long_code())))
+                };
+                for elem in elems {
+                    acc = accumulate(self, func, acc, elem)?;
+                }
+                values![acc]
+            }
+            HigherFn::Any | HigherFn::All => {
+                let want_truthy = self.0 == HigherFn::Any;
+                let mut found = false;
+                for elem in elems {
+                    if step_value(self, func, elem)?.is_truthy() == want_truthy {
+                        found = true;
+                        break
+                    }
+                }
+                let res = if want_truthy { found } else { !found };
+                values![Nr::new(if res { 1.0 } else { 0.0 })]
+            }
+            HigherFn::CountWhere => {
+                let mut count = 0usize;
+                for elem in elems {
+                    if step_value(self, func, elem)?.is_truthy() {
+                        count += 1;
+                    }
+                }
+                values![Nr::from(count)]
+            }
+            HigherFn::FindFirst => {
+                for elem in elems {
+                    if step_value(self, func, elem.fork())?.is_truthy() {
+                        return Ok(values![elem])
+                    }
+                }
+                values![Value::default()]
+            }
+        })
+    }
+}
+
+impl OpTyp for HigherOrder {
+
+    fn description(&self) -> &'static str {
+        match self.0 {
+            HigherFn::Map => "apply the function to each element, and collect the results in an array",
+            HigherFn::FlatMap => "apply the function to each element, and collect all values it leaves in one array, with array results joined",
+            HigherFn::Filter => "keep the elements for which the function gives a truthy result",
+            HigherFn::Reject => "remove the elements for which the function gives a truthy result",
+            HigherFn::Reduce => "combine the elements from first to last by applying the function to the result so far and the next element",
+            HigherFn::Any => "1 if the function gives a truthy result for any element, 0 otherwise",
+            HigherFn::All => "1 if the function gives a truthy result for every element, 0 otherwise",
+            HigherFn::CountWhere => "count the elements for which the function gives a truthy result",
+            HigherFn::FindFirst => "the first element for which the function gives a truthy result, or 0 if there is none",
+        }
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed(match self.0 {
+            HigherFn::Map => "map",
+            HigherFn::FlatMap => "flat-map",
+            HigherFn::Filter => "filter",
+            HigherFn::Reject => "reject",
+            HigherFn::Reduce => "reduce",
+            HigherFn::Any => "any",
+            HigherFn::All => "all",
+            HigherFn::CountWhere => "count-where",
+            HigherFn::FindFirst => "find-first",
+        })
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        use Letter::*;
+        Some(match self.0 {
+            HigherFn::Map => GolfWord::new2(Slash, Hat),
+            HigherFn::FlatMap => GolfWord::new3(Slash, Hat, Hash),
+            HigherFn::Filter => GolfWord::new2(Slash, Question),
+            HigherFn::Reject => GolfWord::new2(Slash, Exclamation),
+            HigherFn::Reduce => GolfWord::new2(Slash, Tilde),
+            HigherFn::Any => GolfWord::new3(Slash, Question, Hat),
+            HigherFn::All => GolfWord::new3(Slash, Question, Tilde),
+            HigherFn::CountWhere => GolfWord::new3(Slash, Question, Hash),
+            HigherFn::FindFirst => GolfWord::new2(Slash, Hash),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Binary(self)
+    }
+}
+
+impl BinaryExecutor for HigherOrder {
+
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number", "number"])
+    }
+
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["number", "text"])
+    }
+
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["number", "array"])
+    }
+
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["text", "number"])
+    }
+
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["text", "text"])
+    }
+
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["text", "array"])
+    }
+
+    fn exec_tf(&self, deep: Text, top: Func) -> TildeRes<Values> {
+        self.apply(chars(&deep).into_iter(), &top, true)
+    }
+
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["array", "number"])
+    }
+
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["array", "text"])
+    }
+
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["array", "array"])
+    }
+
+    fn exec_af(&self, deep: Array, top: Func) -> TildeRes<Values> {
+        self.apply(deep.iter(), &top, false)
+    }
+
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
+    }
+
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
+    }
+
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Fold;
+
+impl Fold {
+    pub fn new() -> Op {
+        Op::of(Fold)
+    }
+}
+
+impl OpTyp for Fold {
+
+    fn description(&self) -> &'static str {
+        "take an array or text, a starting value and a function, and combine the elements from first to last by applying the function to the result so far and the next element"
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed("fold")
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        Some(GolfWord::new3(Letter::Slash, Letter::Tilde, Letter::Hash))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Ternary(self)
+    }
+}
+
+impl TernaryExecutor for Fold {
+
+    fn exec_values(&self, deep: Value, middle: Value, top: Value) -> TildeRes<Values> {
+        let mut acc = middle;
+        match (&deep, &top) {
+            (Value::Arr(arr), Value::Func(func)) => for elem in arr.iter() {
+                acc = accumulate(self, func, acc, elem)?;
+            },
+            (Value::Txt(text), Value::Func(func)) => for elem in chars(text) {
+                acc = accumulate(self, func, acc, elem)?;
+            },
+            _ => return unsupported(self, &[deep.type_name(), acc.type_name(), top.type_name()]),
+        }
+        Ok(values![acc])
+    }
+
+    fn exec_top_f(&self, deep: Value, middle: Value, top: Func) -> TildeRes<Values> {
+        self.exec_values(deep, middle, Value::Func(top))
+    }
+
+    fn exec_double(&self, deep: Value, top: Value) -> TildeRes<Values> {
+        unsupported(self, &[deep.type_name(), top.type_name()])
+    }
+
+    fn exec_single(&self, single: Value) -> TildeRes<Values> {
+        unsupported(self, &[single.type_name()])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::op::testing::arr;
+    use crate::op::testing::nr;
+    use crate::op::testing::run;
+
+    use super::*;
+
+    #[test]
+    fn map_and_flat_map() {
+        assert_eq!(run("drop 4 range { 2 mul } map").unwrap(), arr(&[0.0, 2.0, 4.0, 6.0]));
+        assert_eq!(run("drop 3 range { range } flat-map").unwrap(), arr(&[0.0, 0.0, 1.0]));
+        assert_eq!(run("drop 3 range { dup range } flat-map").unwrap(), arr(&[0.0, 1.0, 0.0, 2.0, 0.0, 1.0]));
+        assert_eq!(run("drop 3 range { drop } flat-map").unwrap(), arr(&[]));
+    }
+
+    #[test]
+    fn map_needs_a_result() {
+        assert!(run("drop 3 range { drop } map count").is_err());
+    }
+
+    #[test]
+    fn filter_and_reject() {
+        assert_eq!(run("drop 6 range { 2 mod } filter").unwrap(), arr(&[1.0, 3.0, 5.0]));
+        assert_eq!(run("drop 6 range { 2 mod } reject").unwrap(), arr(&[0.0, 2.0, 4.0]));
+        assert_eq!(run("drop 'a-b-c' { '-' neq } filter").unwrap(), Value::Txt(Text::of("abc")));
+    }
+
+    #[test]
+    fn reduce_and_fold() {
+        assert_eq!(run("drop 5 range-to { mul } reduce").unwrap(), nr(120.0));
+        assert_eq!(run("drop 4 range { minus } reduce").unwrap(), nr(-6.0));
+        assert!(run("drop 3 3 minus range { plus } reduce").is_err());
+        assert_eq!(run("drop 3 3 minus range 7 { plus } fold").unwrap(), nr(7.0));
+        assert_eq!(run("drop 'abc' '>' { swap concat } fold").unwrap(), Value::Txt(Text::of("cba>")));
+    }
+
+    #[test]
+    fn predicates() {
+        assert_eq!(run("drop 5 range { 3 gt } any").unwrap(), nr(1.0));
+        assert_eq!(run("drop 5 range { 3 gt } all").unwrap(), nr(0.0));
+        assert_eq!(run("drop 5 range-to { 1 gte } all").unwrap(), nr(1.0));
+        assert_eq!(run("drop 3 3 minus range { 3 gt } all").unwrap(), nr(1.0));
+        assert_eq!(run("drop 10 range { 3 mod } count-where").unwrap(), nr(6.0));
+        assert_eq!(run("drop 10 range { 3 gt } find-first").unwrap(), nr(4.0));
+        assert_eq!(run("drop 3 range { 3 gt } find-first").unwrap(), nr(0.0));
+    }
+
+    #[test]
+    fn map_and_filter_are_lazy() {
+        assert_eq!(run("drop 1000000000000 range-to { 1 plus } map 3 lookup").unwrap(), nr(5.0));
+        assert_eq!(run("drop 1000000000000 range { 2 mul } flat-map 3 lookup").unwrap(), nr(6.0));
+        assert_eq!(run("drop 1000000000000 range { 2 mod } filter 1 lookup").unwrap(), nr(3.0));
+        assert_eq!(run("drop 1000000000000 range { 2 mod } reject 1 lookup").unwrap(), nr(2.0));
+    }
+
+    #[test]
+    fn stops_at_first_match() {
+        assert_eq!(run("drop 1000000000000 range { 3 gt } find-first").unwrap(), nr(4.0));
+        assert_eq!(run("drop 1000000000000 range { 3 gt } any").unwrap(), nr(1.0));
+    }
+}
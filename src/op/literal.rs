@@ -6,11 +6,15 @@ use ::tinyvec::TinyVec;
 use crate::common::escape_for_string;
 use crate::compile::encode_str;
 use crate::compile::GolfWord;
+use crate::compile::Letter;
+use crate::compile::Prog;
 use crate::exec::{Executor, NullaryExecutor};
+use crate::Func;
 use crate::Nr;
 use crate::op::Op;
 use crate::op::OpTyp;
 use crate::Text;
+use crate::TildeRes;
 use crate::Values;
 use crate::values;
 
@@ -54,8 +58,8 @@ impl TextOp {
 }
 
 impl NullaryExecutor for TextOp {
-    fn exec(&self) -> Values {
-        values![self.0.clone()]
+    fn exec(&self) -> TildeRes<Values> {
+        Ok(values![self.0.clone()])
     }
 }
 
@@ -85,8 +89,8 @@ impl OpTyp for NumberOp {
 }
 
 impl NullaryExecutor for NumberOp {
-    fn exec(&self) -> Values {
-        values![self.0]
+    fn exec(&self) -> TildeRes<Values> {
+        Ok(values![self.0])
     }
 }
 
@@ -99,3 +103,62 @@ impl NumberOp {
         Op::of(Self::new_pure(nr))
     }
 }
+
+/// A block of code between braces, which pushes a closure that runs the block on its argument.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlockOp(Prog);
+
+impl OpTyp for BlockOp {
+    fn description(&self) -> &'static str {
+        "Pushes a closure that runs the operations in the block on its argument"
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        if self.0.len() == 0 {
+            return Cow::Borrowed("{ }")
+        }
+        Cow::Owned(format!("{{ {} }}", self.0.long_code()))
+    }
+
+    /// Blocks contain other operations, so [Prog] encodes them using [BlockOp::golf_open] and [BlockOp::golf_close].
+    fn golf_code(&self) -> Option<GolfWord> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Nullary(self)
+    }
+}
+
+impl BlockOp {
+    pub fn new_pure(prog: Prog) -> Self {
+        BlockOp(prog)
+    }
+
+    pub fn new(prog: Prog) -> Op {
+        Op::of(Self::new_pure(prog))
+    }
+
+    pub fn prog(&self) -> &Prog {
+        &self.0
+    }
+
+    pub fn golf_open() -> GolfWord {
+        GolfWord::new2(Letter::Seq, Letter::Bracket)
+    }
+
+    /// Can be left out at the end of the program.
+    pub fn golf_close() -> GolfWord {
+        GolfWord::new2(Letter::Seq, Letter::Right)
+    }
+}
+
+impl NullaryExecutor for BlockOp {
+    fn exec(&self) -> TildeRes<Values> {
+        Ok(values![Func::of_prog(&self.0)])
+    }
+}
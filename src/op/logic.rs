@@ -0,0 +1,251 @@
+use ::std::any::Any;
+use ::std::borrow::Cow;
+
+use crate::Array;
+use crate::compile::GolfWord;
+use crate::compile::Letter;
+use crate::exec::BinaryExecutor;
+use crate::exec::dispatch_unary;
+use crate::exec::Executor;
+use crate::exec::UnaryExecutor;
+use crate::exec::unsupported;
+use crate::Nr;
+use crate::op::Op;
+use crate::op::OpTyp;
+use crate::Text;
+use crate::TildeRes;
+use crate::Value;
+use crate::Values;
+use crate::values;
+
+fn bool_nr(value: bool) -> Nr {
+    Nr::new(if value { 1.0 } else { 0.0 })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Not;
+
+impl Not {
+    pub fn new() -> Op {
+        Op::of(Not)
+    }
+}
+
+impl OpTyp for Not {
+
+    fn description(&self) -> &'static str {
+        "1 if the value is falsy (zero or empty), 0 otherwise. Arrays are negated element by element."
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed("not")
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        Some(GolfWord::new2(Letter::Asterisk, Letter::Exclamation))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Unary(self)
+    }
+}
+
+impl UnaryExecutor for Not {
+
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        Ok(values![bool_nr(!Value::Num(value).is_truthy())])
+    }
+
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
+        Ok(values![bool_nr(!Value::Txt(value).is_truthy())])
+    }
+
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
+        let mut new = Vec::new();
+        for elem in value.iter() {
+            new.extend(dispatch_unary(self, Some(elem))?);
+        }
+        Ok(values![Array::of(new)])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Connective {
+    And,
+    Or,
+    Xor,
+    Nand,
+    Implies,
+}
+
+/// Combine the truthiness of two values, giving 1 or 0. Arrays are combined element by element, and a
+/// non-array combined with an array is combined with each of its elements.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Logic(Connective);
+
+impl Logic {
+    pub fn new(connective: Connective) -> Op {
+        Op::of(Logic(connective))
+    }
+
+    fn combine(&self, deep: Value, top: Value) -> TildeRes<Values> {
+        let (deep, top) = (deep.is_truthy(), top.is_truthy());
+        Ok(values![bool_nr(match self.0 {
+            Connective::And => deep && top,
+            Connective::Or => deep || top,
+            Connective::Xor => deep != top,
+            Connective::Nand => !(deep && top),
+            Connective::Implies => !deep || top,
+        })])
+    }
+}
+
+impl OpTyp for Logic {
+
+    fn description(&self) -> &'static str {
+        match self.0 {
+            Connective::And => "1 if both values are truthy, 0 otherwise",
+            Connective::Or => "1 if either value is truthy, 0 otherwise",
+            Connective::Xor => "1 if exactly one of the values is truthy, 0 otherwise",
+            Connective::Nand => "0 if both values are truthy, 1 otherwise",
+            Connective::Implies => "0 if the deeper value is truthy and the top one is not, 1 otherwise",
+        }
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed(match self.0 {
+            Connective::And => "and",
+            Connective::Or => "or",
+            Connective::Xor => "xor",
+            Connective::Nand => "nand",
+            Connective::Implies => "implies",
+        })
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        use Letter::*;
+        Some(match self.0 {
+            Connective::And => GolfWord::new1(Asterisk),
+            Connective::Or => GolfWord::new2(Asterisk, Hat),
+            Connective::Xor => GolfWord::new2(Asterisk, Question),
+            Connective::Nand => GolfWord::new2(Asterisk, Tilde),
+            Connective::Implies => GolfWord::new2(Asterisk, Hash),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Binary(self)
+    }
+}
+
+impl BinaryExecutor for Logic {
+
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        self.combine(Value::Num(deep), Value::Num(top))
+    }
+
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        self.combine(Value::Num(deep), Value::Txt(top))
+    }
+
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        self.exec_each(top, Value::Num(deep), false)
+    }
+
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        self.combine(Value::Txt(deep), Value::Num(top))
+    }
+
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        self.combine(Value::Txt(deep), Value::Txt(top))
+    }
+
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        self.exec_each(top, Value::Txt(deep), false)
+    }
+
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        self.exec_each(deep, Value::Num(top), true)
+    }
+
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        self.exec_each(deep, Value::Txt(top), true)
+    }
+
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        self.exec_elemwise(deep, top)
+    }
+
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
+    }
+
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
+    }
+
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::op::testing::nr;
+    use crate::op::testing::nrs;
+    use crate::op::testing::run;
+
+    use super::*;
+
+    #[test]
+    fn not_values() {
+        assert_eq!(run("drop 3 not").unwrap(), nr(0.0));
+        assert_eq!(run("drop 3 3 minus not").unwrap(), nr(1.0));
+        assert_eq!(run("drop '' not").unwrap(), nr(1.0));
+        assert_eq!(run("drop 'a' not").unwrap(), nr(0.0));
+    }
+
+    #[test]
+    fn not_array_each() {
+        assert_eq!(Not.exec_a(nrs(&[0.0, 2.0])).unwrap(), values![nrs(&[1.0, 0.0])]);
+    }
+
+    #[test]
+    fn truth_tables() {
+        let cases = [
+            (Connective::And, [0.0, 0.0, 0.0, 1.0]),
+            (Connective::Or, [0.0, 1.0, 1.0, 1.0]),
+            (Connective::Xor, [0.0, 1.0, 1.0, 0.0]),
+            (Connective::Nand, [1.0, 1.0, 1.0, 0.0]),
+            (Connective::Implies, [1.0, 1.0, 0.0, 1.0]),
+        ];
+        for (connective, expected) in cases {
+            let res = Logic(connective).exec_aa(nrs(&[0.0, 0.0, 1.0, 1.0]), nrs(&[0.0, 1.0, 0.0, 1.0])).unwrap();
+            assert_eq!(res, values![nrs(&expected)], "{connective:?}");
+        }
+    }
+
+    #[test]
+    fn mixed_types() {
+        assert_eq!(run("drop 'a' 2 and").unwrap(), nr(1.0));
+        assert_eq!(run("drop '' 2 2 minus or").unwrap(), nr(0.0));
+        assert_eq!(Logic(Connective::And).exec_na(Nr::new(1.0), nrs(&[0.0, 5.0])).unwrap(), values![nrs(&[0.0, 1.0])]);
+        assert_eq!(Logic(Connective::Implies).exec_an(nrs(&[0.0, 5.0]), Nr::zero()).unwrap(), values![nrs(&[1.0, 0.0])]);
+    }
+}
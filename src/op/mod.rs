@@ -1,12 +1,36 @@
+use crate::op::arithmetic::BinaryFn;
+use crate::op::arithmetic::BinaryMath;
 use crate::op::arithmetic::Sqrt;
+use crate::op::arithmetic::UnaryFn;
+use crate::op::arithmetic::UnaryMath;
 use crate::op::collection::{Count, Flatten};
 use crate::op::collection::Last;
 use crate::op::collection::Lookup;
 use crate::op::collection::Split;
 use crate::op::collection::Sum;
 use crate::op::collection::Unique;
+use crate::op::compare::Cmp;
+use crate::op::compare::Compare;
+use crate::op::control::If;
+use crate::op::control::Times;
+use crate::op::control::UntilStable;
+use crate::op::control::While;
 pub use crate::op::func::Apply;
+use crate::op::effects::Print;
+use crate::op::effects::Return;
 use crate::op::func::Arg;
+use crate::op::higher::Fold;
+use crate::op::higher::HigherFn;
+use crate::op::higher::HigherOrder;
+use crate::op::logic::Connective;
+use crate::op::logic::Logic;
+use crate::op::logic::Not;
+use crate::op::range::Range;
+use crate::op::range::RangeBetween;
+use crate::op::range::RangeKind;
+use crate::op::range::RangeStep;
+use crate::op::recursion::Fix;
+use crate::op::recursion::Recurse;
 use crate::op::sanitize::BaseWords;
 pub use crate::op::stack::Drop;
 pub use crate::op::stack::Duplicate;
@@ -18,10 +42,15 @@ pub use self::arithmetic::IntDiv;
 pub use self::arithmetic::Minus;
 pub use self::arithmetic::Mul;
 pub use self::arithmetic::Plus;
+pub use self::literal::BlockOp;
 pub use self::literal::NumberOp;
 pub use self::literal::TextOp;
 pub use self::typ::Op;
 pub use self::typ::OpTyp;
+pub use self::variable::is_register;
+pub use self::variable::Load;
+pub use self::variable::lookup_register_golf;
+pub use self::variable::Store;
 
 mod typ;
 mod literal;
@@ -31,8 +60,18 @@ mod collection;
 mod sanitize;
 mod func;
 mod text;
+mod control;
+mod variable;
+mod recursion;
+mod effects;
+mod compare;
+mod logic;
+mod range;
+mod higher;
+#[cfg(test)]
+pub(crate) mod testing;
 
-pub fn all_non_literals() -> [Op; 20] {
+pub fn all_non_literals() -> [Op; 76] {
     //TODO @mark:
     [
         Op::of(Plus),
@@ -55,6 +94,62 @@ pub fn all_non_literals() -> [Op; 20] {
         Op::of(Sum),
         Op::of(Swap),
         Op::of(Flatten),
+        Op::of(If),
+        Op::of(While),
+        Op::of(Times),
+        Op::of(UntilStable),
+        Fix::new(),
+        Fix::memo(),
+        Op::of(Recurse),
+        Op::of(Print),
+        Op::of(Return),
+        Compare::new(Cmp::Eq),
+        Compare::new(Cmp::Neq),
+        Compare::new(Cmp::Lt),
+        Compare::new(Cmp::Lte),
+        Compare::new(Cmp::Gt),
+        Compare::new(Cmp::Gte),
+        Compare::each(Cmp::Eq),
+        Compare::each(Cmp::Neq),
+        Compare::each(Cmp::Lt),
+        Compare::each(Cmp::Lte),
+        Compare::each(Cmp::Gt),
+        Compare::each(Cmp::Gte),
+        Op::of(Not),
+        Logic::new(Connective::And),
+        Logic::new(Connective::Or),
+        Logic::new(Connective::Xor),
+        Logic::new(Connective::Nand),
+        Logic::new(Connective::Implies),
+        UnaryMath::new(UnaryFn::Neg),
+        UnaryMath::new(UnaryFn::Abs),
+        UnaryMath::new(UnaryFn::Incr),
+        UnaryMath::new(UnaryFn::Decr),
+        UnaryMath::new(UnaryFn::Sign),
+        UnaryMath::new(UnaryFn::Reciprocal),
+        UnaryMath::new(UnaryFn::Floor),
+        UnaryMath::new(UnaryFn::Ceil),
+        UnaryMath::new(UnaryFn::Round),
+        UnaryMath::new(UnaryFn::Trunc),
+        BinaryMath::new(BinaryFn::Pow),
+        BinaryMath::new(BinaryFn::Mod),
+        BinaryMath::new(BinaryFn::Rem),
+        BinaryMath::new(BinaryFn::RoundTo),
+        Range::new(RangeKind::Upto),
+        Range::new(RangeKind::Through),
+        Range::new(RangeKind::Down),
+        RangeBetween::new(),
+        RangeStep::new(),
+        HigherOrder::new(HigherFn::Map),
+        HigherOrder::new(HigherFn::FlatMap),
+        HigherOrder::new(HigherFn::Filter),
+        HigherOrder::new(HigherFn::Reject),
+        HigherOrder::new(HigherFn::Reduce),
+        HigherOrder::new(HigherFn::Any),
+        HigherOrder::new(HigherFn::All),
+        HigherOrder::new(HigherFn::CountWhere),
+        HigherOrder::new(HigherFn::FindFirst),
+        Fold::new(),
     ]
 }
 
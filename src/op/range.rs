@@ -0,0 +1,341 @@
+use ::std::any::Any;
+use ::std::borrow::Cow;
+
+use crate::Array;
+use crate::compile::GolfWord;
+use crate::compile::Letter;
+use crate::exec::BinaryExecutor;
+use crate::exec::Executor;
+use crate::exec::TernaryExecutor;
+use crate::exec::UnaryExecutor;
+use crate::exec::unsupported;
+use crate::Nr;
+use crate::op::Op;
+use crate::op::OpTyp;
+use crate::Text;
+use crate::TildeError;
+use crate::TildeRes;
+use crate::Value;
+use crate::Values;
+use crate::values;
+
+/// Numbers from `start` towards `end` (exclusive) in steps of `step`, which may be negative to count down.
+/// The elements are produced lazily, so that huge ranges only cost what is used.
+fn stepped(start: Nr, end: Nr, step: Nr) -> TildeRes<Array> {
+    if step == Nr::zero() {
+        return Err(TildeError::runtime("the step of a range cannot be zero"))
+    }
+    let ascending = step > Nr::zero();
+    let elems = (0u64..)
+        .map(move |ix| start.plus(step.mul(Nr::from(ix))))
+        .take_while(move |nr| if ascending { *nr < end } else { *nr > end })
+        .map(Value::Num);
+    Ok(Array::lazy(elems))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RangeKind {
+    /// 0, 1, ..., n-1
+    Upto,
+    /// 1, 2, ..., n
+    Through,
+    /// n-1, n-2, ..., 0
+    Down,
+}
+
+/// Range based on a count. For text the count is the number of characters, and for arrays the number of
+/// elements, which gives the indices.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Range(RangeKind);
+
+impl Range {
+    pub fn new(kind: RangeKind) -> Op {
+        Op::of(Range(kind))
+    }
+
+    fn of_count(&self, count: Nr) -> TildeRes<Values> {
+        let count = Nr::from(count.to_uint()?);
+        let one = Nr::new(1.0);
+        let range = match self.0 {
+            RangeKind::Upto => stepped(Nr::zero(), count, one)?,
+            RangeKind::Through => stepped(one, count.incr(), one)?,
+            RangeKind::Down => stepped(count.decr(), one.neg(), one.neg())?,
+        };
+        Ok(values![range])
+    }
+}
+
+impl OpTyp for Range {
+
+    fn description(&self) -> &'static str {
+        match self.0 {
+            RangeKind::Upto => "numbers from 0 up to but excluding the top number, or the indices of a text or array",
+            RangeKind::Through => "numbers from 1 up to and including the top number, or the indices plus one of a text or array",
+            RangeKind::Down => "numbers from one less than the top number down to 0, or the indices of a text or array from the end",
+        }
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed(match self.0 {
+            RangeKind::Upto => "range",
+            RangeKind::Through => "range-to",
+            RangeKind::Down => "range-down",
+        })
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        use Letter::*;
+        Some(match self.0 {
+            RangeKind::Upto => GolfWord::new1(Bracket),
+            RangeKind::Through => GolfWord::new2(Bracket, Hat),
+            RangeKind::Down => GolfWord::new2(Bracket, Tilde),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Unary(self)
+    }
+}
+
+impl UnaryExecutor for Range {
+
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        self.of_count(value)
+    }
+
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
+        self.of_count(Nr::from(value.as_str().chars().count()))
+    }
+
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
+        self.of_count(Nr::from(value.len()))
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+/// Numbers from the deeper number towards the top one (exclusive), counting down if the top one is smaller.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RangeBetween;
+
+impl RangeBetween {
+    pub fn new() -> Op {
+        Op::of(RangeBetween)
+    }
+}
+
+impl OpTyp for RangeBetween {
+
+    fn description(&self) -> &'static str {
+        "numbers from the deeper number up to but excluding the top number, counting down if the top one is smaller"
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed("range-between")
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        Some(GolfWord::new2(Letter::Bracket, Letter::Question))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Binary(self)
+    }
+}
+
+impl BinaryExecutor for RangeBetween {
+
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        let step = if top < deep { Nr::new(-1.0) } else { Nr::new(1.0) };
+        Ok(values![stepped(deep, top, step)?])
+    }
+
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["number", "text"])
+    }
+
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["number", "array"])
+    }
+
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["text", "number"])
+    }
+
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["text", "text"])
+    }
+
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["text", "array"])
+    }
+
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["array", "number"])
+    }
+
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["array", "text"])
+    }
+
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["array", "array"])
+    }
+
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
+    }
+
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
+    }
+
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RangeStep;
+
+impl RangeStep {
+    pub fn new() -> Op {
+        Op::of(RangeStep)
+    }
+}
+
+impl OpTyp for RangeStep {
+
+    fn description(&self) -> &'static str {
+        "take a start, end and step, and give numbers from the start towards the end (exclusive) in steps of the given size, which is negative to count down"
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed("range-step")
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        Some(GolfWord::new2(Letter::Bracket, Letter::Hash))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Ternary(self)
+    }
+}
+
+impl TernaryExecutor for RangeStep {
+
+    fn exec_values(&self, deep: Value, middle: Value, top: Value) -> TildeRes<Values> {
+        let (Value::Num(start), Value::Num(end), Value::Num(step)) = (&deep, &middle, &top) else {
+            return unsupported(self, &[deep.type_name(), middle.type_name(), top.type_name()])
+        };
+        Ok(values![stepped(*start, *end, *step)?])
+    }
+
+    fn exec_double(&self, deep: Value, top: Value) -> TildeRes<Values> {
+        unsupported(self, &[deep.type_name(), top.type_name()])
+    }
+
+    fn exec_single(&self, single: Value) -> TildeRes<Values> {
+        unsupported(self, &[single.type_name()])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ErrorKind;
+    use crate::ExecConfig;
+    use crate::IoConfig;
+    use crate::op::testing::arr;
+    use crate::op::testing::run;
+    use crate::op::testing::run_with;
+    use crate::RunMode;
+    use crate::tilde_strs_mode;
+
+    use super::*;
+
+    #[test]
+    fn count_ranges() {
+        assert_eq!(run("drop 4 range").unwrap(), arr(&[0.0, 1.0, 2.0, 3.0]));
+        assert_eq!(run("drop 4 range-to").unwrap(), arr(&[1.0, 2.0, 3.0, 4.0]));
+        assert_eq!(run("drop 4 range-down").unwrap(), arr(&[3.0, 2.0, 1.0, 0.0]));
+        assert_eq!(run("drop 3 3 minus range").unwrap(), arr(&[]));
+    }
+
+    #[test]
+    fn count_must_be_whole() {
+        assert!(run("drop 3 2 div range").is_err());
+        assert!(run("drop 2 neg range-to").is_err());
+    }
+
+    #[test]
+    fn indices_of_text_and_array() {
+        assert_eq!(run("drop '你好!' range").unwrap(), arr(&[0.0, 1.0, 2.0]));
+        assert_eq!(run("drop 3 range-to range-down").unwrap(), arr(&[2.0, 1.0, 0.0]));
+    }
+
+    #[test]
+    fn between_both_directions() {
+        assert_eq!(run("drop 2 5 range-between").unwrap(), arr(&[2.0, 3.0, 4.0]));
+        assert_eq!(run("drop 5 2 range-between").unwrap(), arr(&[5.0, 4.0, 3.0]));
+        assert_eq!(run("drop 2 2 range-between").unwrap(), arr(&[]));
+    }
+
+    #[test]
+    fn with_step() {
+        assert_eq!(run("drop 1 10 3 range-step").unwrap(), arr(&[1.0, 4.0, 7.0]));
+        assert_eq!(run("drop 10 1 4 neg range-step").unwrap(), arr(&[10.0, 6.0, 2.0]));
+        assert_eq!(run("drop 1 10 3 neg range-step").unwrap(), arr(&[]));
+        assert_eq!(run("drop 1 2 1 2 div range-step").unwrap(), arr(&[1.0, 1.5]));
+        assert!(run("drop 1 10 3 3 minus range-step").is_err());
+    }
+
+    #[test]
+    fn huge_range_is_lazy() {
+        let Value::Arr(range) = run("drop 1000000000000 range").unwrap() else {
+            panic!("expected an array")
+        };
+        assert_eq!(range.get(2), Some(Value::Num(Nr::new(2.0))));
+        assert!(!range.is_complete());
+    }
+
+    #[test]
+    fn huge_range_stops_at_limits() {
+        let code = "drop 100000 100000 mul range-to count";
+        let config = ExecConfig { max_size: Some(1000), ..ExecConfig::default() };
+        let err = run_with(code, &config).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::SizeLimit { .. }), "{err:?}");
+        let config = ExecConfig { max_steps: Some(1000), ..ExecConfig::default() };
+        let err = run_with(code, &config).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::StepLimit { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn huge_range_output_stops_at_limits() {
+        let config = ExecConfig { max_size: Some(1000), ..ExecConfig::default() };
+        let err = tilde_strs_mode("drop 100000 100000 mul range-to", "", RunMode::Any, &IoConfig::default(), &config).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::SizeLimit { .. }), "{err:?}");
+    }
+}
@@ -0,0 +1,278 @@
+use ::std::any::Any;
+use ::std::borrow::Cow;
+
+use crate::Array;
+use crate::compile::GolfWord;
+use crate::exec::BinaryExecutor;
+use crate::exec::Executor;
+use crate::exec::recurse;
+use crate::exec::run_fixed;
+use crate::exec::UnaryExecutor;
+use crate::exec::unsupported;
+use crate::Func;
+use crate::Nr;
+use crate::op::Op;
+use crate::op::OpTyp;
+use crate::Text;
+use crate::TildeRes;
+use crate::Value;
+use crate::Values;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Fix {
+    memo: bool,
+}
+
+impl Fix {
+    pub fn new() -> Op {
+        Op::of(Fix { memo: false })
+    }
+
+    pub fn memo() -> Op {
+        Op::of(Fix { memo: true })
+    }
+}
+
+impl OpTyp for Fix {
+
+    fn description(&self) -> &'static str {
+        if self.memo {
+            "take a value and a function, and apply the function to the value, where 'recurse' inside the function applies it again. Results are remembered and reused for repeated arguments."
+        } else {
+            "take a value and a function, and apply the function to the value, where 'recurse' inside the function applies it again"
+        }
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        if self.memo {
+            Cow::Borrowed("fix-memo")
+        } else {
+            Cow::Borrowed("fix")
+        }
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Binary(self)
+    }
+}
+
+impl BinaryExecutor for Fix {
+
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number", "number"])
+    }
+
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["number", "text"])
+    }
+
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["number", "array"])
+    }
+
+    fn exec_nf(&self, deep: Nr, top: Func) -> TildeRes<Values> {
+        run_fixed(&top, self.memo, Value::Num(deep))
+    }
+
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["text", "number"])
+    }
+
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["text", "text"])
+    }
+
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["text", "array"])
+    }
+
+    fn exec_tf(&self, deep: Text, top: Func) -> TildeRes<Values> {
+        run_fixed(&top, self.memo, Value::Txt(deep))
+    }
+
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["array", "number"])
+    }
+
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["array", "text"])
+    }
+
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["array", "array"])
+    }
+
+    fn exec_af(&self, deep: Array, top: Func) -> TildeRes<Values> {
+        run_fixed(&top, self.memo, Value::Arr(deep))
+    }
+
+    fn exec_fn(&self, deep: Func, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["function", "number"])
+    }
+
+    fn exec_ft(&self, deep: Func, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["function", "text"])
+    }
+
+    fn exec_fa(&self, deep: Func, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["function", "array"])
+    }
+
+    fn exec_ff(&self, deep: Func, top: Func) -> TildeRes<Values> {
+        run_fixed(&top, self.memo, Value::Func(deep))
+    }
+
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
+    }
+
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
+    }
+
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
+    }
+
+    fn exec_single_f(&self, single: Func) -> TildeRes<Values> {
+        unsupported(self, &["function"])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Recurse;
+
+impl Recurse {
+    pub fn new() -> Op {
+        Op::of(Recurse)
+    }
+}
+
+impl OpTyp for Recurse {
+
+    fn description(&self) -> &'static str {
+        "apply the innermost function that is running through 'fix' to the top value. A function value is not applied but gets the recursion added to it."
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Borrowed("recurse")
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Unary(self)
+    }
+}
+
+impl UnaryExecutor for Recurse {
+
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        recurse(Value::Num(value))
+    }
+
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
+        recurse(Value::Txt(value))
+    }
+
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
+        recurse(Value::Arr(value))
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::std::thread;
+
+    use crate::ErrorKind;
+    use crate::ExecConfig;
+    use crate::MAX_DEPTH;
+    use crate::op::testing::nr;
+    use crate::op::testing::run_with;
+    use crate::RUN_STACK_SIZE;
+
+    #[test]
+    fn factorial() {
+        let config = ExecConfig { max_steps: Some(1000), ..ExecConfig::default() };
+        let res = run_with("drop 5 { { dup 1 minus recurse mul } 1 if } fix", &config).unwrap();
+        assert_eq!(res, nr(120.0));
+    }
+
+    #[test]
+    fn fibonacci_memo() {
+        // without memoization this takes tens of thousands of steps
+        let config = ExecConfig { max_steps: Some(2000), ..ExecConfig::default() };
+        let fib = "{ { 1 minus { dup recurse swap 1 minus recurse plus } 1 if } 3 3 minus if } fix-memo";
+        let res = run_with(&format!("drop 20 {fib}"), &config).unwrap();
+        assert_eq!(res, nr(6765.0));
+    }
+
+    #[test]
+    fn memo_does_not_read_lazy_argument() {
+        let config = ExecConfig { max_steps: Some(1000), ..ExecConfig::default() };
+        let res = run_with("drop 1000000000000 range { 1 lookup } fix-memo", &config).unwrap();
+        assert_eq!(res, nr(1.0));
+    }
+
+    #[test]
+    fn nested_fix_recurses_innermost() {
+        let config = ExecConfig { max_steps: Some(1000), ..ExecConfig::default() };
+        let res = run_with("drop 3 { { 1 minus 1 { { 1 minus recurse 3 plus } if } fix swap recurse plus } 100 if } fix", &config).unwrap();
+        assert_eq!(res, nr(109.0));
+    }
+
+    #[test]
+    fn depth_limit() {
+        let config = ExecConfig { max_depth: Some(20), ..ExecConfig::default() };
+        let err = run_with("drop 1 { recurse } fix", &config).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::DepthLimit { depth: 20 });
+    }
+
+    #[test]
+    fn default_depth_limit_does_not_overflow() {
+        let err = run_with("drop 1 { { 1 plus recurse } 1 if } fix", &ExecConfig::default()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::DepthLimit { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn max_depth_fits_on_run_stack() {
+        let runner = thread::Builder::new().stack_size(RUN_STACK_SIZE).spawn(|| {
+            // higher limits are lowered to the maximum
+            let config = ExecConfig { max_depth: Some(10 * MAX_DEPTH), ..ExecConfig::default() };
+            let count = "{ { 1 minus { { recurse } 1 if } apply 1 plus } 1 if } fix";
+            let res = run_with(&format!("drop {MAX_DEPTH} {count}"), &config).unwrap();
+            assert_eq!(res, nr((MAX_DEPTH + 1) as f64));
+            let err = run_with(&format!("drop {} {count}", MAX_DEPTH + 1), &config).unwrap_err();
+            assert_eq!(err.kind(), &ErrorKind::DepthLimit { depth: MAX_DEPTH });
+        }).unwrap();
+        runner.join().unwrap();
+    }
+
+    #[test]
+    fn recurse_outside_fix() {
+        let err = run_with("drop 1 recurse", &ExecConfig::default()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Runtime(_)), "{err:?}");
+    }
+}
@@ -5,10 +5,12 @@ use crate::Values;
 use crate::Text;
 use crate::Nr;
 use crate::Array;
+use crate::TildeRes;
 use crate::values;
 use crate::compile::GolfWord;
 use crate::exec::Executor;
 use crate::exec::UnaryExecutor;
+use crate::exec::unsupported;
 use crate::op::Op;
 use crate::op::OpTyp;
 use crate::op::collection::Split;
@@ -47,11 +49,11 @@ impl OpTyp for BaseWords {
 
 impl UnaryExecutor for BaseWords {
 
-    fn exec_n(&self, value: Nr) -> Values {
-        values![]
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        Ok(values![])
     }
 
-    fn exec_t(&self, value: Text) -> Values {
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
         let orig_words = Split::split_str(value.as_str());
         let mut san_words = Vec::with_capacity(orig_words.len());
         for mut word in orig_words {
@@ -66,14 +68,14 @@ impl UnaryExecutor for BaseWords {
                 san_words.push(word)
             }
         }
-        values![Array::of(san_words)]
+        Ok(values![Array::of(san_words)])
     }
 
-    fn exec_a(&self, value: Array) -> Values {
-        todo!()
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
\ No newline at end of file
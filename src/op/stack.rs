@@ -7,10 +7,12 @@ use crate::compile::Letter;
 use crate::data::Fork;
 use crate::exec::{BinaryOpaqueExecutor, Executor};
 use crate::exec::UnaryExecutor;
+use crate::exec::unsupported;
 use crate::Nr;
 use crate::op::Op;
 use crate::op::OpTyp;
 use crate::Text;
+use crate::TildeRes;
 use crate::Values;
 use crate::values;
 
@@ -49,6 +51,10 @@ impl OpTyp for Drop {
         self
     }
 
+    fn output_count(&self) -> usize {
+        0
+    }
+
     fn as_executor(&self) -> Executor {
         Executor::Unary(self)
     }
@@ -56,29 +62,29 @@ impl OpTyp for Drop {
 
 impl UnaryExecutor for Drop {
 
-    fn exec_n(&self, value: Nr) -> Values {
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
         // no action, just drop the input
-        values![]
+        Ok(values![])
     }
 
-    fn exec_t(&self, value: Text) -> Values {
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
         // no action, just drop the input
-        values![]
+        Ok(values![])
     }
 
-    fn exec_a(&self, value: Array) -> Values {
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
         // no action, just drop the input
-        values![]
+        Ok(values![])
     }
 
-    // fn exec_f(&self, value: Func) -> Values {
+    // fn exec_f(&self, value: Func) -> TildeRes<Values> {
     //     // no action, just drop the input
     //     values![]
     //     //TODO @mark: is it correct that this applies eagerly instead of lazily like most func operations?
     // }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -109,6 +115,10 @@ impl OpTyp for Duplicate {
         self
     }
 
+    fn output_count(&self) -> usize {
+        2
+    }
+
     fn as_executor(&self) -> Executor {
         Executor::Unary(self)
     }
@@ -116,29 +126,29 @@ impl OpTyp for Duplicate {
 
 impl UnaryExecutor for Duplicate {
 
-    fn exec_n(&self, value: Nr) -> Values {
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
         // duplicate the input
-        values![value, value]
+        Ok(values![value, value])
     }
 
-    fn exec_t(&self, value: Text) -> Values {
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
         // duplicate the input
-        values![value.clone(), value]
+        Ok(values![value.clone(), value])
     }
 
-    fn exec_a(&self, value: Array) -> Values {
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
         // duplicate the input
-        values![value.fork(), value]
+        Ok(values![value.fork(), value])
     }
 
-    // fn exec_f(&self, value: Func) -> Values {
+    // fn exec_f(&self, value: Func) -> TildeRes<Values> {
     //     // duplicate the input
     //     values![value.clone(), value]
     //     //TODO @mark: is it correct that this applies eagerly instead of lazily like most func operations?
     // }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
 
@@ -169,6 +179,10 @@ impl OpTyp for Swap {
         self
     }
 
+    fn output_count(&self) -> usize {
+        2
+    }
+
     fn as_executor(&self) -> Executor {
         Executor::BinaryOpaque(self)
     }
@@ -176,15 +190,15 @@ impl OpTyp for Swap {
 
 impl BinaryOpaqueExecutor for Swap {
 
-    fn exec_opaque(&self, deep: Value, top: Value) -> Values {
-        values![top, deep]
+    fn exec_opaque(&self, deep: Value, top: Value) -> TildeRes<Values> {
+        Ok(values![top, deep])
     }
 
-    fn exec_single_opaque(&self, single: Value) -> Values {
-        values![single]  // noop
+    fn exec_single_opaque(&self, single: Value) -> TildeRes<Values> {
+        Ok(values![single])  // noop
     }
 
-    fn exec_empty(&self) -> Values {
-        Values::new()  // noop
+    fn exec_empty(&self) -> TildeRes<Values> {
+        Ok(Values::new())  // noop
     }
 }
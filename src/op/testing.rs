@@ -0,0 +1,36 @@
+//! Helpers for the tests of operations, which run code through [crate::tilde_eval].
+
+use crate::Array;
+use crate::ExecConfig;
+use crate::Nr;
+use crate::RunMode;
+use crate::tilde_eval;
+use crate::tilde_eval_stack;
+use crate::TildeRes;
+use crate::Value;
+
+/// Run the code on the default input, and give the top of the final stack.
+pub fn run(code: &str) -> TildeRes<Value> {
+    run_with(code, &ExecConfig::default())
+}
+
+pub fn run_with(code: &str, config: &ExecConfig) -> TildeRes<Value> {
+    tilde_eval(code, Value::default(), RunMode::Any, config)
+}
+
+/// Run the code on the default input, and give the whole final stack.
+pub fn run_stack(code: &str) -> TildeRes<Vec<Value>> {
+    tilde_eval_stack(code, Value::default(), RunMode::Any, &ExecConfig::default())
+}
+
+pub fn nr(nr: f64) -> Value {
+    Value::Num(Nr::new(nr))
+}
+
+pub fn nrs(nrs: &[f64]) -> Array {
+    Array::of(nrs.iter().map(|nr| Nr::new(*nr)).collect())
+}
+
+pub fn arr(elems: &[f64]) -> Value {
+    Value::Arr(nrs(elems))
+}
@@ -5,10 +5,12 @@ use crate::Text;
 use crate::values;
 use crate::Nr;
 use crate::Array;
+use crate::TildeRes;
 use crate::Values;
 use crate::compile::GolfWord;
 use crate::exec::BinaryExecutor;
 use crate::exec::Executor;
+use crate::exec::unsupported;
 use crate::op::Op;
 use crate::op::OpTyp;
 
@@ -45,55 +47,55 @@ impl OpTyp for Concat {
 }
 
 impl BinaryExecutor for Concat {
-    fn exec_nn(&self, deep: Nr, top: Nr) -> Values {
-        values![Value::Txt(Text::of(format!("{deep}{top}")))]
+    fn exec_nn(&self, deep: Nr, top: Nr) -> TildeRes<Values> {
+        Ok(values![Value::Txt(Text::of(format!("{deep}{top}")))])
     }
 
-    fn exec_nt(&self, deep: Nr, top: Text) -> Values {
-        values![Value::Txt(Text::of(format!("{deep}{top}")))]
+    fn exec_nt(&self, deep: Nr, top: Text) -> TildeRes<Values> {
+        Ok(values![Value::Txt(Text::of(format!("{deep}{top}")))])
     }
 
-    fn exec_na(&self, deep: Nr, top: Array) -> Values {
-        todo!()
+    fn exec_na(&self, deep: Nr, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["number", "array"])
     }
 
-    fn exec_tn(&self, deep: Text, top: Nr) -> Values {
-        values![Value::Txt(Text::of(format!("{deep}{top}")))]
+    fn exec_tn(&self, deep: Text, top: Nr) -> TildeRes<Values> {
+        Ok(values![Value::Txt(Text::of(format!("{deep}{top}")))])
     }
 
-    fn exec_tt(&self, deep: Text, top: Text) -> Values {
-        values![Value::Txt(Text::of(format!("{deep}{top}")))]
+    fn exec_tt(&self, deep: Text, top: Text) -> TildeRes<Values> {
+        Ok(values![Value::Txt(Text::of(format!("{deep}{top}")))])
     }
 
-    fn exec_ta(&self, deep: Text, top: Array) -> Values {
-        todo!()
+    fn exec_ta(&self, deep: Text, top: Array) -> TildeRes<Values> {
+        unsupported(self, &["text", "array"])
     }
 
-    fn exec_an(&self, deep: Array, top: Nr) -> Values {
-        todo!()
+    fn exec_an(&self, deep: Array, top: Nr) -> TildeRes<Values> {
+        unsupported(self, &["array", "number"])
     }
 
-    fn exec_at(&self, deep: Array, top: Text) -> Values {
-        todo!()
+    fn exec_at(&self, deep: Array, top: Text) -> TildeRes<Values> {
+        unsupported(self, &["array", "text"])
     }
 
-    fn exec_aa(&self, deep: Array, top: Array) -> Values {
+    fn exec_aa(&self, deep: Array, top: Array) -> TildeRes<Values> {
         self.exec_elemwise(deep, top)
     }
 
-    fn exec_single_n(&self, single: Nr) -> Values {
-        todo!()
+    fn exec_single_n(&self, single: Nr) -> TildeRes<Values> {
+        unsupported(self, &["number"])
     }
 
-    fn exec_single_t(&self, single: Text) -> Values {
-        todo!()
+    fn exec_single_t(&self, single: Text) -> TildeRes<Values> {
+        unsupported(self, &["text"])
     }
 
-    fn exec_single_a(&self, single: Array) -> Values {
-        todo!()
+    fn exec_single_a(&self, single: Array) -> TildeRes<Values> {
+        unsupported(self, &["array"])
     }
 
-    fn exec_empty(&self) -> Values {
-        todo!()
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
     }
 }
\ No newline at end of file
@@ -29,6 +29,12 @@ pub trait OpTyp: fmt::Debug + OpClone + OpEq {
 
     fn as_any(&self) -> &dyn Any;
 
+    /// How many values the operation leaves on the stack, which closures use to know which values
+    /// later operations will take. Operations whose output count varies should give the usual one.
+    fn output_count(&self) -> usize {
+        1
+    }
+
     //TODO @mark: can we do without this somehow? Maybe Op should already contain executor info?
     fn as_executor(&self) -> Executor;
 }
@@ -0,0 +1,149 @@
+use ::std::any::Any;
+use ::std::borrow::Cow;
+
+use crate::Array;
+use crate::compile::GolfWord;
+use crate::compile::Letter;
+use crate::data::Fork;
+use crate::exec::Executor;
+use crate::exec::load_var;
+use crate::exec::NullaryExecutor;
+use crate::exec::store_var;
+use crate::exec::unsupported;
+use crate::exec::UnaryExecutor;
+use crate::Func;
+use crate::Nr;
+use crate::op::Op;
+use crate::op::OpTyp;
+use crate::Text;
+use crate::TildeRes;
+use crate::Values;
+use crate::values;
+
+/// Variables with these names are registers, which have a golf code with the matching modifier from [Letter::modifiers].
+const REGISTER_NAMES: [&str; 5] = ["a", "b", "c", "d", "e"];
+
+fn register_golf_code(opener: Letter, name: &str) -> Option<GolfWord> {
+    REGISTER_NAMES.iter()
+        .position(|register| *register == name)
+        .map(|ix| GolfWord::new2(opener, Letter::modifiers()[ix]))
+}
+
+fn register_from_golf(opener: Letter, word: &GolfWord) -> Option<&'static str> {
+    REGISTER_NAMES.iter()
+        .zip(Letter::modifiers())
+        .find(|(_, modifier)| *word == GolfWord::new2(opener, *modifier))
+        .map(|(name, _)| *name)
+}
+
+/// Whether the name can be used to load a variable without storing it first in the source.
+pub fn is_register(name: &str) -> bool {
+    REGISTER_NAMES.contains(&name)
+}
+
+/// Find a register store or load by golf code.
+pub fn lookup_register_golf(word: &GolfWord) -> Option<Op> {
+    if let Some(name) = register_from_golf(Letter::More, word) {
+        return Some(Store::new(name))
+    }
+    register_from_golf(Letter::Io, word).map(Load::new)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Store(String);
+
+impl Store {
+    pub fn new(name: impl Into<String>) -> Op {
+        Op::of(Store(name.into()))
+    }
+}
+
+impl OpTyp for Store {
+
+    fn description(&self) -> &'static str {
+        "store the top value in a variable, leaving it on the stack"
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Owned(format!("={}", self.0))
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        register_golf_code(Letter::More, &self.0)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Unary(self)
+    }
+}
+
+impl UnaryExecutor for Store {
+
+    fn exec_n(&self, value: Nr) -> TildeRes<Values> {
+        store_var(&self.0, value.into());
+        Ok(values![value])
+    }
+
+    fn exec_t(&self, value: Text) -> TildeRes<Values> {
+        store_var(&self.0, value.clone().into());
+        Ok(values![value])
+    }
+
+    fn exec_a(&self, value: Array) -> TildeRes<Values> {
+        store_var(&self.0, value.fork().into());
+        Ok(values![value])
+    }
+
+    fn exec_f(&self, value: Func) -> TildeRes<Values> {
+        // store the function itself, instead of adding the store to it
+        store_var(&self.0, value.fork().into());
+        Ok(values![value])
+    }
+
+    fn exec_empty(&self) -> TildeRes<Values> {
+        unsupported(self, &[])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Load(String);
+
+impl Load {
+    pub fn new(name: impl Into<String>) -> Op {
+        Op::of(Load(name.into()))
+    }
+}
+
+impl OpTyp for Load {
+
+    fn description(&self) -> &'static str {
+        "push the value of a variable, or the program input if it was not stored yet"
+    }
+
+    fn long_code(&self) -> Cow<'static, str> {
+        Cow::Owned(self.0.clone())
+    }
+
+    fn golf_code(&self) -> Option<GolfWord> {
+        register_golf_code(Letter::Io, &self.0)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_executor(&self) -> Executor {
+        Executor::Nullary(self)
+    }
+}
+
+impl NullaryExecutor for Load {
+
+    fn exec(&self) -> TildeRes<Values> {
+        Ok(values![load_var(&self.0)])
+    }
+}
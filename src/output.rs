@@ -0,0 +1,221 @@
+use ::std::io;
+use ::std::io::Write;
+use ::std::slice;
+
+use crate::Value;
+
+/// How the final stack is turned into output text.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum OutputMode {
+    /// Only the top value of the stack, using its normal text representation.
+    #[default]
+    Top,
+    /// Every value on the stack on its own line, from the bottom to the top.
+    Stack,
+    /// The top value, and if it is an array, each element on its own line. Nested arrays are flattened
+    /// and their elements are separated by spaces. Every line ends with a newline.
+    Lines,
+    /// The whole stack as a json array from bottom to top, where functions are objects with a `function` key.
+    Json,
+}
+
+impl OutputMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "top" => Some(OutputMode::Top),
+            "stack" => Some(OutputMode::Stack),
+            "lines" => Some(OutputMode::Lines),
+            "json" => Some(OutputMode::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Write the final stack, with the top value last, as output text. In [OutputMode::Lines], lazy arrays
+/// are written and flushed one element at a time, so that output can appear while input arrives.
+pub fn write_output(out: &mut impl Write, stack: &[Value], mode: OutputMode) -> io::Result<()> {
+    match mode {
+        OutputMode::Top => write_top(out, stack),
+        OutputMode::Stack => {
+            for (i, value) in stack.iter().enumerate() {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                write!(out, "{value}")?;
+            }
+            Ok(())
+        },
+        OutputMode::Lines => match stack.last() {
+            Some(Value::Arr(arr)) => {
+                let is_streaming = !arr.is_complete();
+                for value in arr.iter() {
+                    write_flat(out, &value)?;
+                    writeln!(out)?;
+                    if is_streaming {
+                        out.flush()?;
+                    }
+                }
+                Ok(())
+            },
+            _ => {
+                write_top(out, stack)?;
+                writeln!(out)
+            },
+        },
+        OutputMode::Json => {
+            write!(out, "[")?;
+            for (i, value) in stack.iter().enumerate() {
+                if i > 0 {
+                    write!(out, ",")?;
+                }
+                write_json(out, value)?;
+            }
+            write!(out, "]")
+        },
+    }
+}
+
+/// Write a value that the program prints while running, formatted like a final stack with only that
+/// value, so that it matches the final output. Every printed value is followed by a newline, so that
+/// consecutive values stay apart. In [OutputMode::Json], each printed value is a separate json document,
+/// and the final stack comes after them.
+pub fn write_printed(out: &mut impl Write, value: &Value, mode: OutputMode) -> io::Result<()> {
+    match mode {
+        OutputMode::Json => write_json(out, value)?,
+        _ => write_output(out, slice::from_ref(value), mode)?,
+    }
+    if ends_with_newline(mode) {
+        return Ok(())
+    }
+    writeln!(out)
+}
+
+/// Whether the output of this mode already ends with a newline.
+pub fn ends_with_newline(mode: OutputMode) -> bool {
+    mode == OutputMode::Lines
+}
+
+fn write_top(out: &mut impl Write, stack: &[Value]) -> io::Result<()> {
+    match stack.last() {
+        Some(top) => write!(out, "{top}"),
+        None => write!(out, "{}", Value::default()),
+    }
+}
+
+fn write_flat(out: &mut impl Write, value: &Value) -> io::Result<()> {
+    match value {
+        Value::Arr(arr) => {
+            for (i, value) in arr.iter().enumerate() {
+                if i > 0 {
+                    write!(out, " ")?;
+                }
+                write_flat(out, &value)?;
+            }
+            Ok(())
+        },
+        value => write!(out, "{value}"),
+    }
+}
+
+fn write_json(out: &mut impl Write, value: &Value) -> io::Result<()> {
+    match value {
+        Value::Num(nr) => write!(out, "{nr}"),
+        Value::Txt(text) => write_json_str(out, text.as_str()),
+        Value::Arr(arr) => {
+            write!(out, "[")?;
+            for (i, value) in arr.iter().enumerate() {
+                if i > 0 {
+                    write!(out, ",")?;
+                }
+                write_json(out, &value)?;
+            }
+            write!(out, "]")
+        },
+        Value::Func(func) => {
+            write!(out, "{{\"function\":")?;
+            write_json_str(out, &format!("{func:?}"))?;
+            write!(out, "}}")
+        },
+    }
+}
+
+fn write_json_str(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write!(out, "\"")?;
+    for ch in text.chars() {
+        match ch {
+            '"' => write!(out, "\\\"")?,
+            '\\' => write!(out, "\\\\")?,
+            '\n' => write!(out, "\\n")?,
+            '\r' => write!(out, "\\r")?,
+            '\t' => write!(out, "\\t")?,
+            ch if ch.is_control() => write!(out, "\\u{:04x}", ch as u32)?,
+            ch => write!(out, "{ch}")?,
+        }
+    }
+    write!(out, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Array;
+    use crate::Nr;
+    use crate::Text;
+
+    use super::*;
+
+    fn format_output(stack: &[Value], mode: OutputMode) -> String {
+        let mut out = Vec::new();
+        write_output(&mut out, stack, mode).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn stack() -> Vec<Value> {
+        vec![
+            Value::Txt(Text::of("a \"b\"\n")),
+            Value::Arr(Array::of(vec![
+                Value::Num(Nr::new(1.0)),
+                Value::Arr(Array::of(vec![Nr::new(2.0), Nr::new(3.5)])),
+                Value::Txt(Text::of("x")),
+            ])),
+        ]
+    }
+
+    #[test]
+    fn top() {
+        assert_eq!(format_output(&stack(), OutputMode::Top), "[1,[2,3.5],x]");
+        assert_eq!(format_output(&[], OutputMode::Top), "0");
+    }
+
+    #[test]
+    fn whole_stack() {
+        assert_eq!(format_output(&stack(), OutputMode::Stack), "a \"b\"\n\n[1,[2,3.5],x]");
+        assert_eq!(format_output(&[], OutputMode::Stack), "");
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(format_output(&stack(), OutputMode::Lines), "1\n2 3.5\nx\n");
+        assert_eq!(format_output(&[Value::Num(Nr::new(4.0))], OutputMode::Lines), "4\n");
+    }
+
+    #[test]
+    fn printed_values_are_separated() {
+        let printed = |mode| {
+            let mut out = Vec::new();
+            for nr in [1.0, 2.0] {
+                write_printed(&mut out, &Value::Num(Nr::new(nr)), mode).unwrap();
+            }
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(printed(OutputMode::Top), "1\n2\n");
+        assert_eq!(printed(OutputMode::Stack), "1\n2\n");
+        assert_eq!(printed(OutputMode::Lines), "1\n2\n");
+        assert_eq!(printed(OutputMode::Json), "1\n2\n");
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(format_output(&stack(), OutputMode::Json), "[\"a \\\"b\\\"\\n\",[1,[2,3.5],\"x\"]]");
+        assert_eq!(format_output(&[Value::Txt(Text::of("\u{1}"))], OutputMode::Json), "[\"\\u0001\"]");
+    }
+}
@@ -0,0 +1,287 @@
+use ::std::cell::RefCell;
+use ::std::collections::HashSet;
+use ::std::io;
+use ::std::io::BufRead;
+use ::std::io::Write;
+use ::std::rc::Rc;
+
+use crate::compile::parse;
+use crate::compile::parse_with_names;
+use crate::data::Fork;
+use crate::exec::execute_on_stack;
+use crate::exec::start_printing;
+use crate::exec::Stack;
+use crate::exec::Variables;
+use crate::Array;
+use crate::ExecConfig;
+use crate::OutputMode;
+use crate::RunMode;
+use crate::Text;
+use crate::TildeError;
+use crate::TildeRes;
+use crate::Value;
+
+const REPL_HELP: &str = "\
+Enter long-mode or golf code to run it on the stack. Meta-commands:
+    \\clear   Remove all values from the stack
+    \\input   Read lines until an empty line, and push them as an array of texts that register a also gives
+    \\golf    Show the golf and base64 encoding of all lines run so far
+    \\undo    Undo the last line of code
+    \\help    Show this help text
+    \\quit    Stop the session (end of input also works)";
+
+/// State of an interactive session, where each line of code runs on the stack and variables left by the previous ones.
+#[derive(Debug, Default)]
+struct Session {
+    stack: Vec<Value>,
+    /// Variables stored so far, where unset ones give the last `\input` (like the input of a program).
+    variables: Variables,
+    /// Names of stored variables, which later lines can load.
+    names: HashSet<String>,
+    /// Source lines that ran successfully, and the state before each of them.
+    history: Vec<(String, Snapshot)>,
+}
+
+/// Everything that undo restores.
+#[derive(Debug)]
+struct Snapshot {
+    stack: Vec<Value>,
+    variables: Variables,
+    names: HashSet<String>,
+}
+
+/// Run an interactive session, reading lines of code or meta-commands and printing the stack after each.
+pub fn tilde_repl<R: BufRead, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    config: &ExecConfig,
+) -> TildeRes<()> {
+    let mut session = Session::new();
+    let mut line = String::new();
+    loop {
+        write!(writer, "> ").and_then(|_| writer.flush()).map_err(write_err)?;
+        if !read_line(&mut reader, &mut line)? {
+            return Ok(())
+        }
+        let output = match line.trim() {
+            "" => continue,
+            "\\quit" => return Ok(()),
+            "\\help" => REPL_HELP.to_owned(),
+            "\\clear" => session.clear(),
+            "\\undo" => session.undo(),
+            "\\golf" => session.golf(),
+            "\\input" => {
+                let mut lines = Vec::new();
+                while read_line(&mut reader, &mut line)? && !line.is_empty() {
+                    lines.push(Value::Txt(Text::of(&line)));
+                }
+                session.push_input(lines)
+            },
+            cmd if cmd.starts_with('\\') => format!("unknown command '{cmd}', try \\help"),
+            code => session.run(code, config),
+        };
+        writeln!(writer, "{output}").map_err(write_err)?;
+    }
+}
+
+impl Session {
+    /// Until `\input` is used, the input is empty, like running a program without stdin.
+    fn new() -> Self {
+        Session {
+            variables: Variables::new(Value::Arr(Array::of(Vec::<Value>::new()))),
+            ..Session::default()
+        }
+    }
+
+    fn run(&mut self, code: &str, config: &ExecConfig) -> String {
+        let before = self.snapshot();
+        let prog = match parse_with_names(code, RunMode::Any, &mut self.names) {
+            Ok(prog) => prog,
+            Err(err) => {
+                self.restore(before);
+                return error_str(&err, code)
+            },
+        };
+        let printed = Rc::new(RefCell::new(Vec::new()));
+        let res = {
+            let _printer = start_printing(printed.clone(), OutputMode::Lines);
+            execute_on_stack(&prog, &mut self.stack, &mut self.variables, config)
+        };
+        let printed = String::from_utf8_lossy(&printed.take()).into_owned();
+        match res {
+            Ok(()) => {
+                self.history.push((code.to_owned(), before));
+                printed + &self.stack_str()
+            },
+            Err(err) => {
+                self.restore(before);
+                printed + &error_str(&err, code)
+            },
+        }
+    }
+
+    fn clear(&mut self) -> String {
+        let before = self.snapshot();
+        self.stack.clear();
+        self.history.push((String::new(), before));
+        self.stack_str()
+    }
+
+    fn push_input(&mut self, lines: Vec<Value>) -> String {
+        let before = self.snapshot();
+        let input = Array::of(lines);
+        self.variables.set_unset(Value::Arr(input.fork()));
+        self.stack.push(Value::Arr(input));
+        self.history.push((String::new(), before));
+        self.stack_str()
+    }
+
+    fn undo(&mut self) -> String {
+        match self.history.pop() {
+            Some((_, before)) => {
+                self.restore(before);
+                self.stack_str()
+            },
+            None => "nothing to undo".to_owned(),
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            stack: self.stack.fork(),
+            variables: self.variables.fork(),
+            names: self.names.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.stack = snapshot.stack;
+        self.variables = snapshot.variables;
+        self.names = snapshot.names;
+    }
+
+    fn golf(&self) -> String {
+        let source = self.history.iter()
+            .map(|(code, _)| code.as_str())
+            .filter(|code| !code.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let prog = match parse(&source, RunMode::Any) {
+            Ok(prog) => prog,
+            Err(err) => return error_str(&err, &source),
+        };
+        match (prog.golf_code(), prog.golf_code_b64()) {
+            (Ok(golf), Ok(base64)) => format!("golf: {golf}\nbase64: {base64}"),
+            (Err(err), _) | (_, Err(err)) => error_str(&err, &source),
+        }
+    }
+
+    fn stack_str(&self) -> String {
+        if self.stack.is_empty() {
+            return "(empty)".to_owned()
+        }
+        self.stack.as_debug_str()
+    }
+}
+
+/// Read a line without the line ending into the buffer, returning false at the end of input.
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> TildeRes<bool> {
+    line.clear();
+    let size = reader.read_line(line)
+        .map_err(|err| TildeError::io(format!("failed to read input, err: {err}")))?;
+    while line.ends_with('\n') || line.ends_with('\r') {
+        line.pop().unwrap();
+    }
+    Ok(size > 0)
+}
+
+fn write_err(err: io::Error) -> TildeError {
+    TildeError::io(format!("failed to write output, err: {err}"))
+}
+
+fn error_str(err: &TildeError, code: &str) -> String {
+    match err.source_snippet(code) {
+        Some(snippet) => format!("{err}\n{snippet}"),
+        None => err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::CliOperation;
+    use crate::IoConfig;
+    use crate::run_tilde_with;
+    use crate::SharedWriter;
+    use crate::TildeArgs;
+
+    use super::*;
+
+    fn repl(input: &str) -> String {
+        let mut output = vec![];
+        tilde_repl(io::Cursor::new(input), &mut output, &ExecConfig::default()).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn keeps_stack_between_lines() {
+        assert_eq!(repl("1 2\nplus\n"), "> 1 | 2\n> 3\n> ");
+    }
+
+    #[test]
+    fn error_restores_stack() {
+        let out = repl("1 'a'\nminus\ndrop\n");
+        assert!(out.starts_with("> 1 | \"a\"\n> operation 'minus' does not support"), "{out}");
+        assert!(out.ends_with("> 1\n> "), "{out}");
+    }
+
+    #[test]
+    fn undo_and_clear() {
+        assert_eq!(repl("1\n2\n\\undo\n\\clear\n\\undo\n\\undo\n\\undo\n"),
+            "> 1\n> 1 | 2\n> 1\n> (empty)\n> 1\n> (empty)\n> nothing to undo\n> ");
+    }
+
+    #[test]
+    fn printed_before_stack() {
+        assert_eq!(repl("1 print 2 print\n"), "> 1\n2\n1 | 2\n> ");
+    }
+
+    #[test]
+    fn push_input() {
+        assert_eq!(repl("\\input\nhello\nworld\n\ncount\n"), "> [\"hello\",\"world\"]\n> 2\n> ");
+    }
+
+    #[test]
+    fn variables_between_lines() {
+        assert_eq!(repl("5 =x\ndrop x 1 plus\n\\undo\n\\undo\nx\n"),
+            "> 5\n> 6\n> 5\n> (empty)\n> could not find an identifier by name 'x' (at source position 0-1)\nx\n^\n> ");
+    }
+
+    #[test]
+    fn register_reads_input() {
+        assert_eq!(repl("a\n\\input\nhi\n\ndrop a\n"), "> []\n> [] | [\"hi\"]\n> [] | [\"hi\"]\n> ");
+    }
+
+    #[test]
+    fn undo_input() {
+        assert_eq!(repl("1\n\\input\nhello\n\n\\undo\n"), "> 1\n> 1 | [\"hello\"]\n> 1\n> ");
+    }
+
+    #[test]
+    fn golf_encoding() {
+        assert_eq!(repl("1 2 drop\ndrop\n\\golf\n"), "> 1\n> (empty)\n> operation NumberOp(1) has no golf representation (at source position 0-1)\n1 2 drop\n^\n> ");
+        assert_eq!(repl("'a' drop\n\\golf\n"), "> (empty)\n> golf: '0'/\nbase64: _vU\n> ");
+    }
+
+    #[test]
+    fn golf_through_cli() {
+        let args = TildeArgs {
+            operation: CliOperation::Repl,
+            config: ExecConfig::default(),
+            io: IoConfig::default(),
+        };
+        let out = SharedWriter::new(Vec::new());
+        run_tilde_with(&args, io::Cursor::new("'abc'\n\\golf\n"), out.clone()).unwrap();
+        let out = String::from_utf8(out.0.take()).unwrap();
+        assert!(out.contains("> golf: "), "{out}");
+    }
+}